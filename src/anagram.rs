@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use log::info;
+
+use crate::dictionary::{DictionaryError, DictionaryErrorKind};
+use crate::scores;
+use crate::words::WordList;
+
+/// Most letters rearranged at once, as every subset of them is looked up.
+pub const MAX_LETTERS: usize = 16;
+
+/// Shortest word listed, leaving out single letters.
+const MIN_WORD_LENGTH: usize = 2;
+
+/// Most words listed for one length.
+const MAX_GROUP_WORDS: usize = 40;
+
+/// Words spelled with some or all of the given letters, longest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anagrams {
+    pub letters: String,
+    /// Words of each length with their Scrabble scores, best first.
+    pub groups: Vec<(String, Vec<(String, u32)>)>,
+}
+
+impl Anagrams {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+/// Letters of `word` in alphabetical order, shared by all of its anagrams.
+fn signature(word: &str) -> String {
+    let mut letters: Vec<char> = word.chars().collect();
+    letters.sort_unstable();
+    letters.into_iter().collect()
+}
+
+/// Words of a word list keyed by their signature, so that the anagrams of some letters are found
+/// by looking up the signatures of their subsets.
+#[derive(Debug, Default)]
+pub struct AnagramIndex {
+    signatures: HashMap<String, Vec<String>>,
+}
+
+impl AnagramIndex {
+    /// Indexes the words made of letters only.
+    pub fn new(words: &[String]) -> Self {
+        let mut index = AnagramIndex::default();
+        for word in words.iter().filter(|word| word.chars().all(char::is_alphabetic)) {
+            index.signatures.entry(signature(word)).or_default().push(word.clone());
+        }
+        index
+    }
+
+    /// Index over the shared [`WordList`].
+    pub fn global() -> &'static AnagramIndex {
+        static INDEX: OnceLock<AnagramIndex> = OnceLock::new();
+        INDEX.get_or_init(|| {
+            let index = AnagramIndex::new(WordList::global().words());
+            info!("Indexed {} letter signatures for anagrams", index.signatures.len());
+            index
+        })
+    }
+
+    /// Words spelled with some or all of `letters`, each letter used at most as often as given.
+    fn find(&self, letters: &str) -> Vec<&str> {
+        let mut counts: Vec<(char, usize)> = Vec::new();
+        for c in signature(letters).chars() {
+            match counts.last_mut() {
+                Some((last, count)) if *last == c => *count += 1,
+                _ => counts.push((c, 1)),
+            }
+        }
+
+        let mut found = Vec::new();
+        let mut subset = String::new();
+        self.find_subsets(&counts, &mut subset, &mut found);
+        found
+    }
+
+    /// Looks up every signature made of `subset` followed by some of the letters of `counts`.
+    fn find_subsets<'a>(&'a self, counts: &[(char, usize)], subset: &mut String, found: &mut Vec<&'a str>) {
+        let Some(((c, count), rest)) = counts.split_first() else {
+            if let Some(words) = self.signatures.get(subset.as_str()) {
+                found.extend(words.iter().map(String::as_str));
+            }
+            return;
+        };
+        let length = subset.len();
+        for taken in 0..=*count {
+            if taken > 0 {
+                subset.push(*c);
+            }
+            self.find_subsets(rest, subset, found);
+        }
+        subset.truncate(length);
+    }
+}
+
+/// Full and partial anagrams of the letters of `text`, grouped by length, longest first and then
+/// by Scrabble score. The text itself is left out.
+pub fn anagrams(text: &str, index: &AnagramIndex) -> Result<Anagrams, DictionaryError> {
+    let text = text.trim().to_lowercase();
+    let letters: String = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.chars().count() > MAX_LETTERS {
+        return Err(DictionaryError {
+            kind: DictionaryErrorKind::InvalidInput,
+            message: format!("At most {} letters can be rearranged", MAX_LETTERS),
+        });
+    }
+
+    let mut words: Vec<(usize, u32, &str)> = index.find(&letters).into_iter()
+        .filter(|word| *word != text && word.chars().count() >= MIN_WORD_LENGTH)
+        .map(|word| (word.chars().count(), scores::scrabble(word), word))
+        .collect();
+    words.sort_by(|(a_length, a_score, a), (b_length, b_score, b)| b_length.cmp(a_length).then(b_score.cmp(a_score)).then(a.cmp(b)));
+
+    let full = letters.chars().count();
+    let mut groups: Vec<(String, Vec<(String, u32)>)> = Vec::new();
+    let mut current = None;
+    for (length, score, word) in words {
+        if current != Some(length) {
+            let label = if length == full { "full anagrams".to_string() } else { format!("{} letters", length) };
+            groups.push((label, Vec::new()));
+            current = Some(length);
+        }
+        let group = &mut groups.last_mut().expect("a group was just added").1;
+        if group.len() < MAX_GROUP_WORDS {
+            group.push((word.to_string(), score));
+        }
+    }
+    Ok(Anagrams { letters, groups })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> AnagramIndex {
+        let words: Vec<String> = ["listen", "silent", "enlist", "tinsel", "lens", "nest", "tin", "is", "a", "list", "lists", "zit"]
+            .iter().map(|word| word.to_string()).collect();
+        AnagramIndex::new(&words)
+    }
+
+    #[test]
+    fn test_full_and_sub_anagrams() {
+        let found = anagrams(" Listen ", &index()).unwrap();
+        assert_eq!(found.letters, "listen");
+        assert_eq!(found.groups, vec![
+            ("full anagrams".to_string(), vec![("enlist".to_string(), 6), ("silent".to_string(), 6), ("tinsel".to_string(), 6)]),
+            ("4 letters".to_string(), vec![("lens".to_string(), 4), ("list".to_string(), 4), ("nest".to_string(), 4)]),
+            ("3 letters".to_string(), vec![("tin".to_string(), 3)]),
+            ("2 letters".to_string(), vec![("is".to_string(), 2)]),
+        ]);
+    }
+
+    #[test]
+    fn test_letters_are_used_once() {
+        let found = anagrams("tsil", &index()).unwrap();
+        assert_eq!(found.groups[0].1[0].0, "list");
+        assert!(!found.groups.iter().flat_map(|(_, words)| words).any(|(word, _)| word == "lists"));
+        assert!(anagrams("qqq", &index()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_too_many_letters() {
+        let error = anagrams("abcdefghijklmnopq", &index()).unwrap_err();
+        assert_eq!(error.kind, DictionaryErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,73 @@
+use std::env;
+
+use log::warn;
+
+use crate::lookup::{parse_chain, Source, DEFAULT_CHAIN};
+use crate::telegram::Format;
+
+/// Bot wide settings read from the environment at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub format: Format,
+    /// Sources tried in order by plain lookups and `/info`.
+    pub fallback_chain: Vec<Source>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            format: Format::default(),
+            fallback_chain: DEFAULT_CHAIN.to_vec(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Config {
+            format: parse_format(env::var("WORDBOT_PARSE_MODE").ok().as_deref()),
+            fallback_chain: parse_fallback_chain(env::var("WORDBOT_FALLBACK_CHAIN").ok().as_deref()),
+        }
+    }
+}
+
+fn parse_fallback_chain(value: Option<&str>) -> Vec<Source> {
+    match value {
+        None => DEFAULT_CHAIN.to_vec(),
+        Some(value) => parse_chain(value).unwrap_or_else(|err| {
+            warn!("Invalid WORDBOT_FALLBACK_CHAIN {:?}: {}, using the default", value, err);
+            DEFAULT_CHAIN.to_vec()
+        }),
+    }
+}
+
+fn parse_format(value: Option<&str>) -> Format {
+    match value.map(|v| v.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("markdownv2") | Some("markdown") => Format::MarkdownV2,
+        Some("html") => Format::Html,
+        Some(other) => {
+            warn!("Unknown WORDBOT_PARSE_MODE {:?}, using MarkdownV2", other);
+            Format::MarkdownV2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse_format(None), Format::MarkdownV2);
+        assert_eq!(parse_format(Some("HTML")), Format::Html);
+        assert_eq!(parse_format(Some(" markdownv2 ")), Format::MarkdownV2);
+        assert_eq!(parse_format(Some("bbcode")), Format::MarkdownV2);
+    }
+
+    #[test]
+    fn test_parse_fallback_chain() {
+        assert_eq!(parse_fallback_chain(None), DEFAULT_CHAIN.to_vec());
+        assert_eq!(parse_fallback_chain(Some("dictionary")), vec![Source::Dictionary]);
+        assert_eq!(parse_fallback_chain(Some("thesaurus")), DEFAULT_CHAIN.to_vec());
+    }
+}
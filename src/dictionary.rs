@@ -1,13 +1,20 @@
+pub mod glossary;
 mod rest;
+pub mod wiktionary;
+pub mod wordnet;
 
 use std::fmt;
 use serde::Deserialize;
 use crate::dictionary::rest::api_dictionary::get_definition;
+use crate::dictionary::rest::merriam_webster::get_mw_definition;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Definition {
     pub(crate) word: String,
     pub(crate) meanings: Vec<Meaning>,
+    /// Where the word comes from, when the source knows.
+    #[serde(default)]
+    pub(crate) origin: Option<String>,
 }
 
 impl fmt::Display for Definition {
@@ -21,11 +28,19 @@ impl fmt::Display for Definition {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Meaning {
     #[serde(rename(deserialize = "partOfSpeech"))]
     pub(crate) part_of_speech: String,
     pub(crate) definitions: Vec<DefinitionDetail>,
+    /// Synonyms of the part of speech as a whole, on top of those of single senses.
+    #[serde(default)]
+    pub(crate) synonyms: Vec<String>,
+    #[serde(default)]
+    pub(crate) antonyms: Vec<String>,
+    /// Inflected forms, such as "plural dogs".
+    #[serde(default)]
+    pub(crate) inflections: Vec<String>,
 }
 
 impl fmt::Display for Meaning {
@@ -39,12 +54,18 @@ impl fmt::Display for Meaning {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct DefinitionDetail {
     pub(crate) definition: String,
     pub(crate) antonyms: Option<Vec<String>>,
     pub(crate) synonyms: Option<Vec<String>>,
     pub(crate) example: Option<String>,
+    /// Broader terms, as "canine" is for "dog".
+    #[serde(default)]
+    pub(crate) hypernyms: Vec<String>,
+    /// Narrower terms, as "puppy" is for "dog".
+    #[serde(default)]
+    pub(crate) hyponyms: Vec<String>,
 }
 
 impl fmt::Display for DefinitionDetail {
@@ -74,12 +95,19 @@ pub enum DictionaryErrorKind {
     NotFound,
     Parse,
     Unknown,
+    /// The text cannot be looked up, such as a link or a whole paragraph.
+    InvalidInput,
 }
 
 pub async fn definition(word: &str) -> Result<Vec<Definition>, DictionaryError> {
     get_definition(word).await
 }
 
+/// Definitions from the Merriam-Webster API, which needs `MERRIAM_WEBSTER_KEY`.
+pub async fn merriam_webster(word: &str) -> Result<Vec<Definition>, DictionaryError> {
+    get_mw_definition(word).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+use crate::dictionary::{Definition, DefinitionDetail, DictionaryError, DictionaryErrorKind, Meaning};
+
+/// Local dictionary of terms the public ones lack, such as team jargon.
+///
+/// Every line holds `term<TAB>part of speech<TAB>definition`, optionally followed by a tab and an
+/// example. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default)]
+pub struct Glossary {
+    entries: HashMap<String, Vec<Meaning>>,
+}
+
+impl Glossary {
+    pub fn parse(source: &str) -> Self {
+        let mut glossary = Glossary::default();
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let (term, part_of_speech, definition) = match fields[..] {
+                [term, part_of_speech, definition, ..] if !term.is_empty() && !definition.is_empty() => (term, part_of_speech, definition),
+                _ => {
+                    warn!("Skipping malformed glossary line {}", number + 1);
+                    continue;
+                }
+            };
+            let detail = DefinitionDetail {
+                definition: definition.to_string(),
+                antonyms: None,
+                synonyms: None,
+                example: fields.get(3).filter(|example| !example.is_empty()).map(|example| example.to_string()),
+                hypernyms: Vec::new(),
+                hyponyms: Vec::new(),
+            };
+            let meanings = glossary.entries.entry(term.to_lowercase()).or_default();
+            match meanings.iter_mut().find(|meaning| meaning.part_of_speech == part_of_speech) {
+                Some(meaning) => meaning.definitions.push(detail),
+                None => meanings.push(Meaning {
+                    part_of_speech: part_of_speech.to_string(),
+                    definitions: vec![detail],
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                }),
+            }
+        }
+        glossary
+    }
+
+    /// Glossary loaded from the file named by `WORDBOT_GLOSSARY`; empty when it is not set.
+    pub fn global() -> &'static Glossary {
+        static GLOSSARY: OnceLock<Glossary> = OnceLock::new();
+        GLOSSARY.get_or_init(|| match env::var("WORDBOT_GLOSSARY") {
+            Ok(path) => match fs::read_to_string(&path) {
+                Ok(source) => {
+                    info!("Loaded glossary from {}", path);
+                    Glossary::parse(&source)
+                }
+                Err(err) => {
+                    warn!("Failed to read glossary {}: {}", path, err);
+                    Glossary::default()
+                }
+            },
+            Err(_) => Glossary::default(),
+        })
+    }
+
+    /// Every term with its senses, in no particular order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[Meaning])> {
+        self.entries.iter().map(|(term, meanings)| (term.as_str(), meanings.as_slice()))
+    }
+
+    pub fn contains(&self, term: &str) -> bool {
+        self.entries.contains_key(&term.trim().to_lowercase())
+    }
+
+    /// Entry for `word`, with its senses grouped by part of speech in file order.
+    pub fn definition(&self, word: &str) -> Result<Vec<Definition>, DictionaryError> {
+        match self.entries.get(&word.trim().to_lowercase()) {
+            Some(meanings) => Ok(vec![Definition { word: word.trim().to_string(), meanings: meanings.clone(), origin: None }]),
+            None => Err(DictionaryError {
+                kind: DictionaryErrorKind::NotFound,
+                message: "".to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# team glossary\n\
+        LGTM\tabbreviation\tLooks good to me.\n\
+        \n\
+        yak shaving\tnoun\tA chain of small tasks needed before the real one.\tTwo hours of yak shaving later, the build works.\n\
+        lgtm\tverb\tTo approve a change.\n\
+        broken line\n";
+
+    #[test]
+    fn test_groups_senses_by_part_of_speech() {
+        let definitions = Glossary::parse(SOURCE).definition("Lgtm").unwrap();
+        let parts: Vec<&str> = definitions[0].meanings.iter().map(|meaning| meaning.part_of_speech.as_str()).collect();
+        assert_eq!(definitions[0].word, "Lgtm");
+        assert_eq!(parts, vec!["abbreviation", "verb"]);
+    }
+
+    #[test]
+    fn test_keeps_examples() {
+        let definitions = Glossary::parse(SOURCE).definition("yak shaving").unwrap();
+        let detail = &definitions[0].meanings[0].definitions[0];
+        assert_eq!(detail.example.as_deref(), Some("Two hours of yak shaving later, the build works."));
+    }
+
+    #[test]
+    fn test_unknown_term_is_not_found() {
+        let error = Glossary::parse(SOURCE).definition("broken line").unwrap_err();
+        assert_eq!(error.kind, DictionaryErrorKind::NotFound);
+    }
+}
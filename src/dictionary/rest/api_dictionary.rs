@@ -1,9 +1,19 @@
 
+use reqwest::Url;
+
 use crate::dictionary::{Definition, DictionaryError, DictionaryErrorKind};
 
+const ENTRIES_URL: &str = "https://api.dictionaryapi.dev/api/v2/entries/en";
+
+/// Entry URL of `word`, percent encoded as a single path segment.
+fn entry_url(word: &str) -> Url {
+    let mut url = Url::parse(ENTRIES_URL).expect("valid entries URL");
+    url.path_segments_mut().expect("base URL").push(word);
+    url
+}
+
 pub async fn get_definition(word: &str) -> Result<Vec<Definition>, DictionaryError> {
-    let url = format!("https://api.dictionaryapi.dev/api/v2/entries/en/{}", word);
-    let result = reqwest::get(&url).await;
+    let result = reqwest::get(entry_url(word)).await;
 
     if result.is_err() {
         return Err(DictionaryError {
@@ -43,6 +53,13 @@ pub async fn get_definition(word: &str) -> Result<Vec<Definition>, DictionaryErr
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_entry_url_encodes_word() {
+        assert_eq!(entry_url("hello").as_str(), "https://api.dictionaryapi.dev/api/v2/entries/en/hello");
+        assert_eq!(entry_url("a/b?c#d").as_str(), "https://api.dictionaryapi.dev/api/v2/entries/en/a%2Fb%3Fc%23d");
+        assert_eq!(entry_url("hello world").as_str(), "https://api.dictionaryapi.dev/api/v2/entries/en/hello%20world");
+    }
+
     #[tokio::test]
     async fn test_get_hello_definition() {
         let word = "hello";
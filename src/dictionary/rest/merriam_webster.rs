@@ -0,0 +1,320 @@
+use std::env;
+
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::dictionary::{Definition, DefinitionDetail, DictionaryError, DictionaryErrorKind, Meaning};
+
+const BASE_URL: &str = "https://www.dictionaryapi.com/api/v3/references";
+
+/// Dictionaries of the Merriam-Webster API sharing one entry format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reference {
+    Collegiate,
+    Learners,
+}
+
+impl Reference {
+    fn from_env() -> Reference {
+        match env::var("MERRIAM_WEBSTER_DICTIONARY").map(|value| value.trim().to_lowercase()).as_deref() {
+            Ok("learners") | Ok("learner's") => Reference::Learners,
+            _ => Reference::Collegiate,
+        }
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Reference::Collegiate => "collegiate",
+            Reference::Learners => "learners",
+        }
+    }
+}
+
+/// Entry URL of `word` in `reference`, under `base` which a local stub may stand in for.
+fn entry_url(base: &str, reference: Reference, word: &str, key: &str) -> Result<Url, DictionaryError> {
+    let mut url = Url::parse(base).map_err(|_| DictionaryError {
+        kind: DictionaryErrorKind::Unknown,
+        message: format!("Invalid Merriam-Webster URL {}", base),
+    })?;
+    url.path_segments_mut()
+        .map_err(|_| DictionaryError {
+            kind: DictionaryErrorKind::Unknown,
+            message: format!("Invalid Merriam-Webster URL {}", base),
+        })?
+        .pop_if_empty()
+        .extend([reference.path(), "json", word]);
+    url.query_pairs_mut().append_pair("key", key);
+    Ok(url)
+}
+
+/// Looks `word` up with the key of `MERRIAM_WEBSTER_KEY`, in the dictionary named by
+/// `MERRIAM_WEBSTER_DICTIONARY` and at `MERRIAM_WEBSTER_URL` when set.
+pub async fn get_mw_definition(word: &str) -> Result<Vec<Definition>, DictionaryError> {
+    let key = match env::var("MERRIAM_WEBSTER_KEY") {
+        Ok(key) if !key.is_empty() => key,
+        _ => return Err(DictionaryError {
+            kind: DictionaryErrorKind::Unknown,
+            message: "MERRIAM_WEBSTER_KEY is not set".to_string(),
+        }),
+    };
+    let base = env::var("MERRIAM_WEBSTER_URL").unwrap_or_else(|_| BASE_URL.to_string());
+    get_definition_from(entry_url(&base, Reference::from_env(), word, &key)?, word).await
+}
+
+async fn get_definition_from(url: Url, word: &str) -> Result<Vec<Definition>, DictionaryError> {
+    let result = reqwest::get(url).await;
+
+    if result.is_err() {
+        return Err(DictionaryError {
+            kind: DictionaryErrorKind::Unknown,
+            message: "Unknown error occurred".to_string(),
+        });
+    }
+
+    let response = result.unwrap();
+
+    if response.status().is_client_error() {
+        return Err(DictionaryError {
+            kind: DictionaryErrorKind::Unknown,
+            message: "Merriam-Webster refused the request".to_string(),
+        });
+    }
+
+    if response.status().is_server_error() {
+        return Err(DictionaryError {
+            kind: DictionaryErrorKind::Unknown,
+            message: "Unknown server error occurred".to_string(),
+        });
+    }
+
+    match response.text().await {
+        Ok(body) => parse_entries(&body, word),
+        Err(_) => Err(DictionaryError {
+            kind: DictionaryErrorKind::Parse,
+            message: "Error parsing the response".to_string(),
+        }),
+    }
+}
+
+/// Turns an API response into one definition of `word`, with a meaning per entry whose headword is
+/// `word`. Unknown words come back as a list of suggested spellings, which counts as not found.
+fn parse_entries(body: &str, word: &str) -> Result<Vec<Definition>, DictionaryError> {
+    let not_found = || DictionaryError {
+        kind: DictionaryErrorKind::NotFound,
+        message: "Word not found".to_string(),
+    };
+    let entries: Vec<Value> = serde_json::from_str(body).map_err(|_| DictionaryError {
+        kind: DictionaryErrorKind::Parse,
+        message: "Error parsing the response".to_string(),
+    })?;
+    let entries: Vec<&Value> = entries.iter().filter(|entry| entry.is_object()).collect();
+    if entries.is_empty() {
+        return Err(not_found());
+    }
+
+    let headword = |entry: &Value| entry["hwi"]["hw"].as_str().unwrap_or_default().replace('*', "");
+    let matching: Vec<&Value> = entries.iter().copied().filter(|entry| headword(entry).eq_ignore_ascii_case(word)).collect();
+    let entries = if matching.is_empty() { entries } else { matching };
+
+    let meanings: Vec<Meaning> = entries.iter()
+        .map(|entry| Meaning {
+            part_of_speech: entry["fl"].as_str().unwrap_or("entry").to_string(),
+            definitions: senses(entry).into_iter().filter_map(definition_detail).collect(),
+            synonyms: Vec::new(),
+            antonyms: Vec::new(),
+            inflections: inflections(entry),
+        })
+        .filter(|meaning| !meaning.definitions.is_empty())
+        .collect();
+    if meanings.is_empty() {
+        return Err(not_found());
+    }
+
+    let origin = entries.iter()
+        .find_map(|entry| running_text(&entry["et"]))
+        .filter(|origin| !origin.is_empty());
+    Ok(vec![Definition { word: headword(entries[0]), meanings, origin }])
+}
+
+/// Senses of every sense sequence of an entry, including those of parenthesized sequences and
+/// binding substitutes.
+fn senses(entry: &Value) -> Vec<&Value> {
+    fn collect<'a>(item: &'a Value, senses: &mut Vec<&'a Value>) {
+        match (item[0].as_str(), &item[1]) {
+            (Some("sense"), sense) => senses.push(sense),
+            (Some("bs"), binding) => senses.push(&binding["sense"]),
+            (Some("pseq"), Value::Array(items)) => items.iter().for_each(|item| collect(item, senses)),
+            _ => {}
+        }
+    }
+
+    let mut senses = Vec::new();
+    for section in entry["def"].as_array().into_iter().flatten() {
+        for sequence in section["sseq"].as_array().into_iter().flatten() {
+            for item in sequence.as_array().into_iter().flatten() {
+                collect(item, &mut senses);
+            }
+        }
+    }
+    senses
+}
+
+fn definition_detail(sense: &Value) -> Option<DefinitionDetail> {
+    let definition = running_text(&sense["dt"]).filter(|definition| !definition.is_empty())?;
+    let example = sense["dt"].as_array().into_iter().flatten()
+        .filter(|item| item[0] == "vis")
+        .find_map(|item| item[1][0]["t"].as_str())
+        .map(strip_markup);
+    Some(DefinitionDetail {
+        definition,
+        antonyms: None,
+        synonyms: None,
+        example,
+        hypernyms: Vec::new(),
+        hyponyms: Vec::new(),
+    })
+}
+
+/// Text items of a defining or etymology text, as `[["text", "..."], ["vis", ...]]`, without markup.
+fn running_text(items: &Value) -> Option<String> {
+    let texts: Vec<String> = items.as_array()?.iter()
+        .filter(|item| item[0] == "text")
+        .filter_map(|item| item[1].as_str())
+        .map(strip_markup)
+        .collect();
+    Some(texts.join(" "))
+}
+
+/// Inflected forms listed with their label, such as "plural dogs".
+fn inflections(entry: &Value) -> Vec<String> {
+    entry["ins"].as_array().into_iter().flatten()
+        .filter_map(|inflection| {
+            let form = inflection["if"].as_str()?.replace('*', "");
+            Some(match inflection["il"].as_str() {
+                Some(label) => format!("{} {}", label, form),
+                None => form,
+            })
+        })
+        .collect()
+}
+
+/// Plain text of Merriam-Webster running text: formatting tokens such as `{it}` are dropped, the
+/// bold colon `{bc}` separates senses, links keep their text and cross-reference notes are removed.
+fn strip_markup(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut skipping: Option<String> = None;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let token = &rest[start + 1..start + length];
+        if skipping.is_none() {
+            plain.push_str(&rest[..start]);
+        }
+        rest = &rest[start + length + 1..];
+
+        if let Some(end) = &skipping {
+            if token == end {
+                skipping = None;
+            }
+            continue;
+        }
+        let mut fields = token.split('|');
+        match fields.next().unwrap_or_default() {
+            "bc" => plain.push_str(": "),
+            "ldquo" => plain.push('“'),
+            "rdquo" => plain.push('”'),
+            "gloss" => plain.push('['),
+            "/gloss" => plain.push(']'),
+            "p_br" => plain.push(' '),
+            name @ ("dx" | "dx_def" | "dx_ety" | "ma") => skipping = Some(format!("/{}", name)),
+            "sx" | "a_link" | "d_link" | "i_link" | "et_link" | "mat" | "dxt" => plain.push_str(fields.next().unwrap_or_default()),
+            _ => {}
+        }
+    }
+    if skipping.is_none() {
+        plain.push_str(rest);
+    }
+
+    let plain = plain.split_whitespace().collect::<Vec<_>>().join(" ");
+    plain.trim_start_matches([':', ' ']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const DOG: &str = include_str!("fixtures/merriam_webster_dog.json");
+    const SUGGESTIONS: &str = include_str!("fixtures/merriam_webster_suggestions.json");
+
+    /// Local stand-in for the API answering one request with `body`; returns its base URL.
+    async fn stub(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body,
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}/api/v3/references", address)
+    }
+
+    #[test]
+    fn test_strip_markup() {
+        assert_eq!(strip_markup("{bc}a domestic mammal ({it}Canis familiaris{/it})"), "a domestic mammal (Canis familiaris)");
+        assert_eq!(strip_markup("{bc}a worthless person {bc}{sx|cad||}"), "a worthless person : cad");
+        assert_eq!(strip_markup("{bc}a male dog {dx}compare {dxt|bitch||}{/dx}"), "a male dog");
+        assert_eq!(strip_markup("{ldquo}feet{rdquo} {gloss}slang{/gloss}"), "“feet” [slang]");
+        assert_eq!(strip_markup("from {et_link|dogge|dogge:1} {unknown}"), "from dogge");
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        let definitions = parse_entries(DOG, "dog").unwrap();
+        let definition = &definitions[0];
+        assert_eq!(definition.word, "dog");
+        assert_eq!(definition.origin.as_deref(), Some("Middle English, from Old English docga"));
+
+        let parts_of_speech: Vec<&str> = definition.meanings.iter().map(|meaning| meaning.part_of_speech.as_str()).collect();
+        assert_eq!(parts_of_speech, vec!["noun", "verb"]);
+
+        let noun = &definition.meanings[0];
+        assert_eq!(noun.inflections, vec!["plural dogs"]);
+        assert_eq!(noun.definitions.len(), 5);
+        assert_eq!(noun.definitions[0].definition, "a highly variable domestic mammal (Canis familiaris) closely related to the gray wolf");
+        assert_eq!(noun.definitions[0].example.as_deref(), Some("the dog barked all night"));
+        assert_eq!(noun.definitions[3].definition, "any of various usually simple mechanical devices for holding, gripping, or fastening");
+        assert_eq!(definition.meanings[1].inflections, vec!["dogged", "dogging"]);
+    }
+
+    #[test]
+    fn test_suggestions_are_not_found() {
+        assert_eq!(parse_entries(SUGGESTIONS, "dgo").unwrap_err().kind, DictionaryErrorKind::NotFound);
+        assert_eq!(parse_entries("[]", "dgo").unwrap_err().kind, DictionaryErrorKind::NotFound);
+        assert_eq!(parse_entries("Invalid API key.", "dog").unwrap_err().kind, DictionaryErrorKind::Parse);
+    }
+
+    #[test]
+    fn test_entry_url() {
+        let url = entry_url(BASE_URL, Reference::Learners, "hot dog", "secret").unwrap();
+        assert_eq!(url.as_str(), "https://www.dictionaryapi.com/api/v3/references/learners/json/hot%20dog?key=secret");
+    }
+
+    #[tokio::test]
+    async fn test_get_definition_from_stub() {
+        let base = stub(DOG).await;
+        let url = entry_url(&base, Reference::Collegiate, "dog", "secret").unwrap();
+        let definitions = get_definition_from(url, "dog").await.unwrap();
+        assert_eq!(definitions[0].meanings[1].definitions[0].definition, "to hunt, track, or follow like a hound");
+    }
+}
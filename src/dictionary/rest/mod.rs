@@ -1 +1,2 @@
 pub mod api_dictionary;
+pub mod merriam_webster;
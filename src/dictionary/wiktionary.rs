@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::dictionary::{Definition, DefinitionDetail, DictionaryError, DictionaryErrorKind, Meaning};
+
+/// File of the index directory holding one compact entry per line.
+const ENTRIES_FILE: &str = "entries.jsonl";
+
+/// File of the index directory listing `word<TAB>offset<TAB>length` for every entry, sorted by word.
+const INDEX_FILE: &str = "index.tsv";
+
+/// Language whose entries are preferred when a word exists in several.
+const PREFERRED_LANGUAGE: &str = "English";
+
+/// Most inflected forms shown for a part of speech, as verbs of some languages have dozens.
+const MAX_INFLECTIONS: usize = 6;
+
+/// Entry of a Wiktextract extract, keeping only the fields the bot uses.
+#[derive(Debug, Deserialize)]
+struct RawEntry {
+    word: String,
+    #[serde(default)]
+    lang: String,
+    #[serde(default)]
+    pos: String,
+    etymology_text: Option<String>,
+    #[serde(default)]
+    senses: Vec<RawSense>,
+    #[serde(default)]
+    forms: Vec<RawForm>,
+    #[serde(default)]
+    translations: Vec<Translation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSense {
+    #[serde(default)]
+    glosses: Vec<String>,
+    #[serde(default)]
+    examples: Vec<RawExample>,
+    #[serde(default)]
+    synonyms: Vec<RawWord>,
+    #[serde(default)]
+    antonyms: Vec<RawWord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawExample {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawWord {
+    word: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawForm {
+    form: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Word of another language with the same meaning.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Translation {
+    #[serde(default)]
+    pub lang: String,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub word: String,
+    /// Sense of the translated word it matches, such as "animal".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sense: Option<String>,
+}
+
+/// Entry as stored in the index: one word of one language as one part of speech.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WiktionaryEntry {
+    pub word: String,
+    pub lang: String,
+    pub pos: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etymology: Option<String>,
+    #[serde(default)]
+    pub senses: Vec<Sense>,
+    /// Inflected forms, such as "plural dogs".
+    #[serde(default)]
+    pub inflections: Vec<String>,
+    #[serde(default)]
+    pub translations: Vec<Translation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sense {
+    pub gloss: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub synonyms: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub antonyms: Vec<String>,
+}
+
+impl From<RawEntry> for WiktionaryEntry {
+    fn from(raw: RawEntry) -> Self {
+        let senses = raw.senses.into_iter()
+            .filter_map(|sense| {
+                // Nested senses repeat the glosses of their parents first.
+                let gloss = sense.glosses.last()?.trim().to_string();
+                Some(Sense {
+                    gloss,
+                    example: sense.examples.into_iter().map(|example| example.text).next(),
+                    synonyms: sense.synonyms.into_iter().map(|synonym| synonym.word).collect(),
+                    antonyms: sense.antonyms.into_iter().map(|antonym| antonym.word).collect(),
+                })
+            })
+            .collect();
+        let inflections = raw.forms.into_iter()
+            .filter(|form| form.form != raw.word && !form.tags.is_empty() && !form.tags.iter().any(|tag| tag == "table-tags" || tag == "inflection-template"))
+            .map(|form| format!("{} {}", form.tags.join(" "), form.form))
+            .collect();
+        WiktionaryEntry {
+            word: raw.word,
+            lang: raw.lang,
+            pos: raw.pos,
+            etymology: raw.etymology_text.filter(|text| !text.trim().is_empty()),
+            senses,
+            inflections,
+            translations: raw.translations.into_iter().filter(|translation| !translation.word.is_empty()).collect(),
+        }
+    }
+}
+
+/// Converts the Wiktextract JSONL extract at `input` into an index in the `output` directory,
+/// returning how many entries it holds. Lines that are not entries are skipped.
+pub fn import(input: &Path, output: &Path) -> io::Result<usize> {
+    fs::create_dir_all(output)?;
+    let mut entries = BufWriter::new(File::create(output.join(ENTRIES_FILE))?);
+    let mut index: BTreeMap<String, Vec<(u64, usize)>> = BTreeMap::new();
+    let mut offset = 0;
+
+    for (number, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+        let line = line?;
+        let raw: RawEntry = match serde_json::from_str(&line) {
+            Ok(raw) => raw,
+            Err(err) => {
+                warn!("Skipping Wiktionary line {}: {}", number + 1, err);
+                continue;
+            }
+        };
+        let entry = WiktionaryEntry::from(raw);
+        if entry.senses.is_empty() {
+            continue;
+        }
+
+        let mut record = serde_json::to_vec(&entry)?;
+        record.push(b'\n');
+        entries.write_all(&record)?;
+        index.entry(entry.word.to_lowercase()).or_default().push((offset, record.len()));
+        offset += record.len() as u64;
+    }
+    entries.flush()?;
+
+    let mut count = 0;
+    let mut index_file = BufWriter::new(File::create(output.join(INDEX_FILE))?);
+    for (word, records) in &index {
+        for (offset, length) in records {
+            writeln!(index_file, "{}\t{}\t{}", word, offset, length)?;
+            count += 1;
+        }
+    }
+    index_file.flush()?;
+    Ok(count)
+}
+
+/// Offline dictionary served from an index built by [`import`]. Only the index is held in memory;
+/// entries are read from disk when asked for.
+#[derive(Debug, Default)]
+pub struct Wiktionary {
+    entries: Option<PathBuf>,
+    index: BTreeMap<String, Vec<(u64, usize)>>,
+}
+
+impl Wiktionary {
+    pub fn open(dir: &Path) -> io::Result<Wiktionary> {
+        let mut index: BTreeMap<String, Vec<(u64, usize)>> = BTreeMap::new();
+        for line in fs::read_to_string(dir.join(INDEX_FILE))?.lines() {
+            let mut fields = line.split('\t');
+            let (Some(word), Some(offset), Some(length)) = (fields.next(), fields.next(), fields.next()) else {
+                continue;
+            };
+            if let (Ok(offset), Ok(length)) = (offset.parse(), length.parse()) {
+                index.entry(word.to_string()).or_default().push((offset, length));
+            }
+        }
+        Ok(Wiktionary { entries: Some(dir.join(ENTRIES_FILE)), index })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Index in the directory named by `WORDBOT_WIKTIONARY`; empty when it is not set.
+    pub fn global() -> &'static Wiktionary {
+        static WIKTIONARY: OnceLock<Wiktionary> = OnceLock::new();
+        WIKTIONARY.get_or_init(|| match env::var("WORDBOT_WIKTIONARY") {
+            Ok(dir) => match Wiktionary::open(Path::new(&dir)) {
+                Ok(wiktionary) => {
+                    info!("Loaded Wiktionary index of {} words from {}", wiktionary.index.len(), dir);
+                    wiktionary
+                }
+                Err(err) => {
+                    warn!("Failed to open Wiktionary index {}: {}", dir, err);
+                    Wiktionary::default()
+                }
+            },
+            Err(_) => Wiktionary::default(),
+        })
+    }
+
+    /// Entries of `word` in every language, those of English first.
+    pub fn entries(&self, word: &str) -> Result<Vec<WiktionaryEntry>, DictionaryError> {
+        let (Some(path), Some(records)) = (&self.entries, self.index.get(&word.trim().to_lowercase())) else {
+            return Ok(Vec::new());
+        };
+        let mut file = File::open(path).map_err(read_error)?;
+        let mut entries = Vec::with_capacity(records.len());
+        for &(offset, length) in records {
+            let mut record = vec![0; length];
+            file.seek(SeekFrom::Start(offset)).and_then(|_| file.read_exact(&mut record)).map_err(read_error)?;
+            entries.push(serde_json::from_slice::<WiktionaryEntry>(&record).map_err(|err| DictionaryError {
+                kind: DictionaryErrorKind::Parse,
+                message: err.to_string(),
+            })?);
+        }
+        entries.sort_by_key(|entry| entry.lang != PREFERRED_LANGUAGE);
+        Ok(entries)
+    }
+
+    /// Entries of `word` shaped like a dictionary entry. Entries of other languages are only used
+    /// when there is no English one and then name their language next to the part of speech.
+    pub fn definition(&self, word: &str) -> Result<Vec<Definition>, DictionaryError> {
+        let mut entries = self.entries(word)?;
+        if entries.iter().any(|entry| entry.lang == PREFERRED_LANGUAGE) {
+            entries.retain(|entry| entry.lang == PREFERRED_LANGUAGE);
+        }
+        let Some(first) = entries.first() else {
+            return Err(DictionaryError {
+                kind: DictionaryErrorKind::NotFound,
+                message: "".to_string(),
+            });
+        };
+
+        let word = first.word.clone();
+        let origin = entries.iter().find_map(|entry| entry.etymology.clone());
+        let meanings = entries.into_iter()
+            .map(|entry| Meaning {
+                part_of_speech: match entry.lang.as_str() {
+                    PREFERRED_LANGUAGE => entry.pos,
+                    lang => format!("{} ({})", entry.pos, lang),
+                },
+                definitions: entry.senses.into_iter()
+                    .map(|sense| DefinitionDetail {
+                        definition: sense.gloss,
+                        antonyms: Some(sense.antonyms),
+                        synonyms: Some(sense.synonyms),
+                        example: sense.example,
+                        hypernyms: Vec::new(),
+                        hyponyms: Vec::new(),
+                    })
+                    .collect(),
+                synonyms: Vec::new(),
+                antonyms: Vec::new(),
+                inflections: entry.inflections.into_iter().take(MAX_INFLECTIONS).collect(),
+            })
+            .collect();
+        Ok(vec![Definition { word, meanings, origin }])
+    }
+
+    /// Translations of `word`, only into `language` when given, which may be a name such as
+    /// "French" or a code such as "fr".
+    pub fn translations(&self, word: &str, language: Option<&str>) -> Result<Vec<Translation>, DictionaryError> {
+        let mut translations: Vec<Translation> = Vec::new();
+        for entry in self.entries(word)?.into_iter().filter(|entry| entry.lang == PREFERRED_LANGUAGE) {
+            for translation in entry.translations {
+                let wanted = language.is_none_or(|language| {
+                    translation.lang.eq_ignore_ascii_case(language) || translation.code.eq_ignore_ascii_case(language)
+                });
+                if wanted && !translations.contains(&translation) {
+                    translations.push(translation);
+                }
+            }
+        }
+        Ok(translations)
+    }
+}
+
+fn read_error(err: io::Error) -> DictionaryError {
+    DictionaryError {
+        kind: DictionaryErrorKind::Unknown,
+        message: format!("Failed to read the Wiktionary index: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const EXTRACT: &str = r#"{"word": "dog", "lang": "English", "lang_code": "en", "pos": "noun", "etymology_text": "From Middle English dogge, from Old English docga.", "forms": [{"form": "dogs", "tags": ["plural"]}], "senses": [{"glosses": ["A mammal, Canis familiaris."], "examples": [{"text": "The dog barked."}], "synonyms": [{"word": "hound"}]}, {"glosses": ["A mammal, Canis familiaris.", "A male dog."]}], "translations": [{"lang": "French", "code": "fr", "word": "chien", "sense": "animal"}, {"lang": "German", "code": "de", "word": "Hund", "sense": "animal"}]}
+{"word": "dog", "lang": "English", "lang_code": "en", "pos": "verb", "forms": [{"form": "dogs", "tags": ["present", "singular", "third-person"]}], "senses": [{"glosses": ["To follow persistently."]}]}
+not json
+{"word": "Hund", "lang": "German", "lang_code": "de", "pos": "noun", "senses": [{"glosses": ["dog"]}]}
+{"word": "redirect", "lang": "English", "pos": "noun", "senses": []}
+"#;
+
+    /// Index of `EXTRACT`, along with the directory holding it, which is removed when dropped.
+    fn wiktionary() -> (TempDir, Wiktionary) {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("extract.jsonl");
+        fs::write(&input, EXTRACT).unwrap();
+        assert_eq!(import(&input, dir.path()).unwrap(), 3);
+        let wiktionary = Wiktionary::open(dir.path()).unwrap();
+        (dir, wiktionary)
+    }
+
+    #[test]
+    fn test_definition_from_index() {
+        let (_dir, wiktionary) = wiktionary();
+        let definitions = wiktionary.definition("Dog").unwrap();
+        let definition = &definitions[0];
+        assert_eq!(definition.origin.as_deref(), Some("From Middle English dogge, from Old English docga."));
+        assert_eq!(definition.meanings[0].part_of_speech, "noun");
+        assert_eq!(definition.meanings[0].inflections, vec!["plural dogs"]);
+        assert_eq!(definition.meanings[0].definitions[1].definition, "A male dog.");
+        assert_eq!(definition.meanings[0].definitions[0].example.as_deref(), Some("The dog barked."));
+        assert_eq!(definition.meanings[1].inflections, vec!["present singular third-person dogs"]);
+    }
+
+    #[test]
+    fn test_other_languages_name_their_language() {
+        let (_dir, wiktionary) = wiktionary();
+        let definitions = wiktionary.definition("hund").unwrap();
+        assert_eq!(definitions[0].meanings[0].part_of_speech, "noun (German)");
+        assert_eq!(wiktionary.definition("redirect").unwrap_err().kind, DictionaryErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_translations_by_name_or_code() {
+        let (_dir, wiktionary) = wiktionary();
+        let all = wiktionary.translations("dog", None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].sense.as_deref(), Some("animal"));
+        let french = wiktionary.translations("dog", Some("FR")).unwrap();
+        assert_eq!(french.iter().map(|translation| translation.word.as_str()).collect::<Vec<_>>(), vec!["chien"]);
+        assert_eq!(wiktionary.translations("dog", Some("german")).unwrap()[0].word, "Hund");
+    }
+}
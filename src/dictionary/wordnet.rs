@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+use crate::dictionary::{Definition, DefinitionDetail, DictionaryError, DictionaryErrorKind, Meaning};
+
+/// Parts of speech of a WordNet database, named as in its `data.*` and `index.*` files.
+const PARTS_OF_SPEECH: [&str; 4] = ["noun", "verb", "adj", "adv"];
+
+/// Most narrower terms kept for a sense, as common words have hundreds.
+const MAX_HYPONYMS: usize = 8;
+
+/// Position of a synset: its part of speech and its byte offset in the data file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SynsetId {
+    pos: char,
+    offset: u32,
+}
+
+/// Set of words sharing one sense.
+#[derive(Debug, Clone)]
+pub struct Synset {
+    pub part_of_speech: &'static str,
+    pub words: Vec<String>,
+    pub definition: String,
+    pub examples: Vec<String>,
+    hypernyms: Vec<SynsetId>,
+    hyponyms: Vec<SynsetId>,
+}
+
+/// WordNet database held in memory, answering lookups without the network.
+#[derive(Debug, Default)]
+pub struct WordNet {
+    synsets: HashMap<SynsetId, Synset>,
+    /// Synsets of every word, most common sense first when the index files were read.
+    index: HashMap<String, Vec<SynsetId>>,
+}
+
+impl WordNet {
+    /// Loads the `data.*` files of a WordNet `dict` directory, ordering senses with the `index.*`
+    /// files found next to them.
+    pub fn load(dir: &Path) -> io::Result<WordNet> {
+        let mut wordnet = WordNet::default();
+        for name in PARTS_OF_SPEECH {
+            wordnet.add_data(&fs::read_to_string(dir.join(format!("data.{}", name)))?);
+        }
+        for name in PARTS_OF_SPEECH {
+            if let Ok(source) = fs::read_to_string(dir.join(format!("index.{}", name))) {
+                wordnet.add_index(&source);
+            }
+        }
+        Ok(wordnet)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Database in the directory named by `WORDBOT_WORDNET`; empty when it is not set.
+    pub fn global() -> &'static WordNet {
+        static WORDNET: OnceLock<WordNet> = OnceLock::new();
+        WORDNET.get_or_init(|| match env::var("WORDBOT_WORDNET") {
+            Ok(dir) => match WordNet::load(Path::new(&dir)) {
+                Ok(wordnet) => {
+                    info!("Loaded {} WordNet synsets from {}", wordnet.synsets.len(), dir);
+                    wordnet
+                }
+                Err(err) => {
+                    warn!("Failed to load WordNet from {}: {}", dir, err);
+                    WordNet::default()
+                }
+            },
+            Err(_) => WordNet::default(),
+        })
+    }
+
+    /// Reads the lines of a data file, such as
+    /// `02084071 05 n 02 dog 0 domestic_dog 0 002 @ 02083346 n 0000 ~ 01322604 n 0000 | a member of the genus Canis`.
+    /// License lines, which start with spaces, are skipped.
+    fn add_data(&mut self, source: &str) {
+        for line in source.lines().filter(|line| !line.starts_with(' ') && !line.trim().is_empty()) {
+            match parse_synset(line) {
+                Some((id, synset)) => {
+                    for word in &synset.words {
+                        let senses = self.index.entry(word.to_lowercase()).or_default();
+                        if !senses.contains(&id) {
+                            senses.push(id);
+                        }
+                    }
+                    self.synsets.insert(id, synset);
+                }
+                None => warn!("Skipping malformed WordNet line {}", line.split(' ').next().unwrap_or_default()),
+            }
+        }
+    }
+
+    /// Reads the lines of an index file, such as `dog n 7 5 @ ~ #m #p %p 7 1 02084071 10114209 ...`,
+    /// whose synsets are listed by how common the sense is.
+    fn add_index(&mut self, source: &str) {
+        for line in source.lines().filter(|line| !line.starts_with(' ')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (Some(lemma), Some(pos), Some(count)) = (fields.first(), fields.get(1), fields.get(2)) else {
+                continue;
+            };
+            let (Some(pos), Ok(count)) = (file_pos(pos), count.parse::<usize>()) else {
+                continue;
+            };
+            let offsets = &fields[fields.len().saturating_sub(count)..];
+            let ordered: Vec<SynsetId> = offsets.iter()
+                .filter_map(|offset| offset.parse().ok())
+                .map(|offset| SynsetId { pos, offset })
+                .filter(|id| self.synsets.contains_key(id))
+                .collect();
+
+            // Senses of this part of speech take the place of the first one, keeping the others.
+            let senses = self.index.entry(lemma.replace('_', " ")).or_default();
+            let rest = senses.iter().filter(|id| id.pos == pos && !ordered.contains(id)).copied();
+            let mut block: Vec<SynsetId> = ordered.iter().copied().chain(rest).collect();
+            let mut reordered = Vec::with_capacity(senses.len() + ordered.len());
+            for id in senses.iter() {
+                if id.pos != pos {
+                    reordered.push(*id);
+                } else if !block.is_empty() {
+                    reordered.append(&mut block);
+                }
+            }
+            reordered.append(&mut block);
+            *senses = reordered;
+        }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.index.contains_key(&word.trim().to_lowercase())
+    }
+
+    /// Every synset, in no particular order.
+    pub fn all_synsets(&self) -> impl Iterator<Item = &Synset> {
+        self.synsets.values()
+    }
+
+    /// Senses of `word` in all parts of speech.
+    pub fn synsets(&self, word: &str) -> Vec<&Synset> {
+        self.index.get(&word.trim().to_lowercase())
+            .map(|ids| ids.iter().filter_map(|id| self.synsets.get(id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Broader senses, as "canine" is for "dog".
+    pub fn hypernyms(&self, synset: &Synset) -> Vec<&Synset> {
+        synset.hypernyms.iter().filter_map(|id| self.synsets.get(id)).collect()
+    }
+
+    /// Narrower senses, as "puppy" is for "dog".
+    pub fn hyponyms(&self, synset: &Synset) -> Vec<&Synset> {
+        synset.hyponyms.iter().filter_map(|id| self.synsets.get(id)).collect()
+    }
+
+    /// Senses of `word` shaped like a dictionary entry, grouped by part of speech. The other words
+    /// of each synset are its synonyms and the first word of related synsets its broader and
+    /// narrower terms.
+    pub fn definition(&self, word: &str) -> Result<Vec<Definition>, DictionaryError> {
+        let synsets = self.synsets(word);
+        if synsets.is_empty() {
+            return Err(DictionaryError {
+                kind: DictionaryErrorKind::NotFound,
+                message: "".to_string(),
+            });
+        }
+
+        let word = word.trim().to_lowercase();
+        let mut meanings: Vec<Meaning> = Vec::new();
+        for synset in synsets {
+            let first_words = |synsets: Vec<&Synset>| -> Vec<String> {
+                synsets.iter().filter_map(|synset| synset.words.first().cloned()).collect()
+            };
+            let mut hyponyms = first_words(self.hyponyms(synset));
+            hyponyms.truncate(MAX_HYPONYMS);
+            let detail = DefinitionDetail {
+                definition: synset.definition.clone(),
+                synonyms: Some(synset.words.iter().filter(|other| other.to_lowercase() != word).cloned().collect()),
+                antonyms: None,
+                example: synset.examples.first().cloned(),
+                hypernyms: first_words(self.hypernyms(synset)),
+                hyponyms,
+            };
+            match meanings.iter_mut().find(|meaning| meaning.part_of_speech == synset.part_of_speech) {
+                Some(meaning) => meaning.definitions.push(detail),
+                None => meanings.push(Meaning {
+                    part_of_speech: synset.part_of_speech.to_string(),
+                    definitions: vec![detail],
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                }),
+            }
+        }
+        Ok(vec![Definition { word, meanings, origin: None }])
+    }
+}
+
+/// Part of speech of the file holding a synset; adjective satellites live with the adjectives.
+fn file_pos(pos: &str) -> Option<char> {
+    match pos {
+        "n" | "v" | "a" | "r" => pos.chars().next(),
+        "s" => Some('a'),
+        _ => None,
+    }
+}
+
+fn part_of_speech_label(pos: char) -> &'static str {
+    match pos {
+        'n' => "noun",
+        'v' => "verb",
+        'a' => "adjective",
+        _ => "adverb",
+    }
+}
+
+fn parse_synset(line: &str) -> Option<(SynsetId, Synset)> {
+    let (fields, gloss) = line.split_once(" | ").unwrap_or((line, ""));
+    let mut fields = fields.split_whitespace();
+    let offset = fields.next()?.parse().ok()?;
+    let _lexicographer_file = fields.next()?;
+    let pos = file_pos(fields.next()?)?;
+
+    let word_count = usize::from_str_radix(fields.next()?, 16).ok()?;
+    let mut words = Vec::with_capacity(word_count);
+    for _ in 0..word_count {
+        let word = fields.next()?;
+        let _lex_id = fields.next()?;
+        // Adjectives may carry a syntactic marker, as in "galore(ip)".
+        let word = word.split_once('(').map_or(word, |(word, _)| word);
+        words.push(word.replace('_', " "));
+    }
+
+    let pointer_count: usize = fields.next()?.parse().ok()?;
+    let (mut hypernyms, mut hyponyms) = (Vec::new(), Vec::new());
+    for _ in 0..pointer_count {
+        let symbol = fields.next()?;
+        let target = SynsetId { offset: fields.next()?.parse().ok()?, pos: file_pos(fields.next()?)? };
+        let _source_target = fields.next()?;
+        match symbol {
+            "@" | "@i" => hypernyms.push(target),
+            "~" | "~i" => hyponyms.push(target),
+            _ => {}
+        }
+    }
+
+    let (mut definitions, mut examples) = (Vec::new(), Vec::new());
+    for part in gloss.split(';').map(str::trim).filter(|part| !part.is_empty()) {
+        match part.strip_prefix('"') {
+            Some(example) => examples.push(example.trim_end_matches('"').to_string()),
+            None => definitions.push(part),
+        }
+    }
+
+    let id = SynsetId { pos, offset };
+    Some((id, Synset {
+        part_of_speech: part_of_speech_label(pos),
+        words,
+        definition: definitions.join("; "),
+        examples,
+        hypernyms,
+        hyponyms,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telegram::{Format, RenderOptions, Telegram};
+
+    const DATA_NOUN: &str = "  1 This software and database is being provided to you, the LICENSEE, by
+02083346 05 n 02 canine 0 canid 0 001 ~ 02084071 n 0000 | any of various fissiped mammals with nonretractile claws
+02084071 05 n 03 dog 0 domestic_dog 0 Canis_familiaris 0 003 @ 02083346 n 0000 ~ 01322604 n 0000 ~ 02087122 n 0000 | a member of the genus Canis; \"the dog barked all night\"
+01322604 05 n 01 puppy 0 001 @ 02084071 n 0000 | a young dog
+02087122 05 n 01 hunting_dog 0 001 @ 02084071 n 0000 | a dog used in hunting game
+10114209 18 n 02 frump 0 dog 1 000 | a dull unattractive unpleasant girl or woman; \"she got a reputation as a frump\"; \"she's a real dog\"
+";
+    const DATA_VERB: &str = "01949817 38 v 01 dog 0 000 01 + 02 00 | go after with the intent to catch; \"The policeman chased the mugger down the alley\"
+";
+    const INDEX_NOUN: &str = "dog n 2 1 @ 2 0 10114209 02084071\n";
+
+    fn wordnet() -> WordNet {
+        let mut wordnet = WordNet::default();
+        wordnet.add_data(DATA_NOUN);
+        wordnet.add_data(DATA_VERB);
+        wordnet
+    }
+
+    #[test]
+    fn test_parse_synsets_and_relations() {
+        let wordnet = wordnet();
+        let dog = wordnet.synsets("Dog")[0];
+        assert_eq!(dog.words, vec!["dog", "domestic dog", "Canis familiaris"]);
+        assert_eq!(dog.definition, "a member of the genus Canis");
+        assert_eq!(dog.examples, vec!["the dog barked all night"]);
+        assert_eq!(wordnet.hypernyms(dog)[0].words[0], "canine");
+        assert_eq!(wordnet.hyponyms(dog).len(), 2);
+        assert!(wordnet.contains("hunting dog"));
+    }
+
+    #[test]
+    fn test_index_orders_senses() {
+        let mut wordnet = wordnet();
+        wordnet.add_index(INDEX_NOUN);
+        let senses: Vec<&str> = wordnet.synsets("dog").iter().map(|synset| synset.definition.as_str()).collect();
+        assert_eq!(senses, vec![
+            "a dull unattractive unpleasant girl or woman",
+            "a member of the genus Canis",
+            "go after with the intent to catch",
+        ]);
+    }
+
+    #[test]
+    fn test_definition_groups_by_part_of_speech() {
+        let definitions = wordnet().definition("dog").unwrap();
+        let meanings = &definitions[0].meanings;
+        assert_eq!(meanings.iter().map(|meaning| meaning.part_of_speech.as_str()).collect::<Vec<_>>(), vec!["noun", "verb"]);
+        let detail = &meanings[0].definitions[0];
+        assert_eq!(detail.synonyms.as_deref(), Some(&["domestic dog".to_string(), "Canis familiaris".to_string()][..]));
+        assert_eq!(detail.hypernyms, vec!["canine"]);
+        assert_eq!(detail.hyponyms, vec!["puppy", "hunting dog"]);
+        assert_eq!(WordNet::default().definition("dog").unwrap_err().kind, DictionaryErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_definition_renders_examples() {
+        let definitions = wordnet().definition("frump").unwrap();
+        assert_eq!(
+            definitions[0].build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>[noun]</b>\n- a dull unattractive unpleasant girl or woman\n<i>&quot;she got a reputation as a frump&quot;</i>\n\n",
+        );
+    }
+}
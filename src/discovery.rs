@@ -0,0 +1,128 @@
+use crate::dictionary::wordnet::WordNet;
+use crate::pronunciation::{Likeness, Pronunciations, RhymeQuality};
+use crate::thesaurus::{collect, Relation, Thesaurus};
+
+/// Most words listed in one group, as common endings rhyme with thousands of words.
+const MAX_GROUP_WORDS: usize = 30;
+
+/// Words found for a word, grouped by how they relate to it, each with its syllable count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordGroups {
+    /// What the groups hold, such as "Rhymes for".
+    pub title: &'static str,
+    pub word: String,
+    pub syllables: usize,
+    pub groups: Vec<(String, Vec<(String, usize)>)>,
+}
+
+impl WordGroups {
+    fn new(title: &'static str, word: &str, pronunciations: &Pronunciations) -> Self {
+        WordGroups { title, word: word.to_string(), syllables: pronunciations.syllables(word), groups: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Adds a group of words, fewest syllables first, dropping repeats and the word itself.
+    fn add<'a>(&mut self, label: &str, words: impl IntoIterator<Item = &'a str>, pronunciations: &Pronunciations) {
+        let mut group: Vec<(String, usize)> = Vec::new();
+        for word in words {
+            if !word.eq_ignore_ascii_case(&self.word) && !group.iter().any(|(known, _)| known.eq_ignore_ascii_case(word)) {
+                group.push((word.to_string(), pronunciations.syllables(word)));
+            }
+        }
+        group.sort_by_key(|(word, syllables)| (*syllables, word.to_lowercase()));
+        group.truncate(MAX_GROUP_WORDS);
+        if !group.is_empty() {
+            self.groups.push((label.to_string(), group));
+        }
+    }
+}
+
+/// Rhymes of `word` grouped by quality, or `None` when its pronunciation is not known.
+pub fn rhymes(word: &str, pronunciations: &Pronunciations) -> Option<WordGroups> {
+    if pronunciations.get(word).is_empty() {
+        return None;
+    }
+    let rhymes = pronunciations.rhymes(word);
+    let mut groups = WordGroups::new("Rhymes for", word, pronunciations);
+    for quality in [RhymeQuality::Perfect, RhymeQuality::Near] {
+        groups.add(quality.label(), rhymes.iter().filter(|(_, found)| *found == quality).map(|(word, _)| *word), pronunciations);
+    }
+    Some(groups)
+}
+
+/// Words sounding like `word` grouped by closeness, or `None` when its pronunciation is not known.
+pub fn sounds_like(word: &str, pronunciations: &Pronunciations) -> Option<WordGroups> {
+    if pronunciations.get(word).is_empty() {
+        return None;
+    }
+    let sounds = pronunciations.sounds_like(word);
+    let mut groups = WordGroups::new("Sounds like", word, pronunciations);
+    for likeness in [Likeness::Same, Likeness::Close] {
+        groups.add(likeness.label(), sounds.iter().filter(|(_, found)| *found == likeness).map(|(word, _)| *word), pronunciations);
+    }
+    Some(groups)
+}
+
+/// Words related to `word` in the thesaurus and WordNet: synonyms, antonyms, and broader and
+/// narrower terms.
+pub fn related(word: &str, thesaurus: &Thesaurus, wordnet: &WordNet, pronunciations: &Pronunciations) -> WordGroups {
+    let mut groups = WordGroups::new("Related to", word, pronunciations);
+    let synsets = wordnet.synsets(word);
+
+    let mut synonyms: Vec<String> = flatten(collect(word, &[], Relation::Synonyms, thesaurus).groups);
+    synonyms.extend(synsets.iter().flat_map(|synset| synset.words.iter().cloned()));
+    groups.add("synonyms", synonyms.iter().map(String::as_str), pronunciations);
+
+    let antonyms = flatten(collect(word, &[], Relation::Antonyms, thesaurus).groups);
+    groups.add("antonyms", antonyms.iter().map(String::as_str), pronunciations);
+
+    let broader: Vec<&str> = synsets.iter()
+        .flat_map(|synset| wordnet.hypernyms(synset))
+        .filter_map(|synset| synset.words.first().map(String::as_str))
+        .collect();
+    groups.add("broader", broader, pronunciations);
+
+    let narrower: Vec<&str> = synsets.iter()
+        .flat_map(|synset| wordnet.hyponyms(synset))
+        .filter_map(|synset| synset.words.first().map(String::as_str))
+        .collect();
+    groups.add("narrower", narrower, pronunciations);
+    groups
+}
+
+fn flatten(groups: Vec<(String, Vec<String>)>) -> Vec<String> {
+    groups.into_iter().flat_map(|(_, words)| words).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pronunciations() -> Pronunciations {
+        Pronunciations::parse("CAT  K AE1 T\nHAT  HH AE1 T\nACROBAT  AE1 K R AH0 B AE2 T\nBAD  B AE1 D\nCUT  K AH1 T\n")
+    }
+
+    #[test]
+    fn test_rhymes_grouped_with_syllables() {
+        let groups = rhymes("cat", &pronunciations()).unwrap();
+        assert_eq!(groups.syllables, 1);
+        assert_eq!(groups.groups, vec![
+            ("perfect rhymes".to_string(), vec![("hat".to_string(), 1), ("acrobat".to_string(), 3)]),
+            ("near rhymes".to_string(), vec![("bad".to_string(), 1)]),
+        ]);
+        assert_eq!(rhymes("dog", &pronunciations()), None);
+    }
+
+    #[test]
+    fn test_related_from_thesaurus() {
+        let thesaurus = Thesaurus::parse("happy\tadjective\tglad, cheerful\tsad\nhappy\tadjective\tGlad\t\n");
+        let groups = related("happy", &thesaurus, &WordNet::default(), &pronunciations());
+        assert_eq!(groups.groups, vec![
+            ("synonyms".to_string(), vec![("glad".to_string(), 1), ("cheerful".to_string(), 2)]),
+            ("antonyms".to_string(), vec![("sad".to_string(), 1)]),
+        ]);
+    }
+}
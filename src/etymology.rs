@@ -0,0 +1,179 @@
+use std::iter;
+use std::sync::OnceLock;
+
+use crate::dictionary::{DictionaryError, DictionaryErrorKind};
+use crate::lemma::Lemmatizer;
+use crate::lookup::{definitions, Source};
+use crate::normalize::normalize_term;
+
+const LANGUAGES: &str = include_str!("etymology/languages.txt");
+
+/// Sources whose entries may tell where a word comes from.
+const ETYMOLOGY_SOURCES: [Source; 3] = [Source::Dictionary, Source::MerriamWebster, Source::Wiktionary];
+
+/// Words that may come before a date, as in "late 14th century".
+const DATE_QUALIFIERS: [&str; 7] = ["early ", "mid-", "mid ", "late ", "c. ", "circa ", "ca. "];
+
+/// Where a word comes from, as told by one source.
+pub struct Etymology {
+    pub word: String,
+    pub source: Source,
+    pub text: String,
+}
+
+/// Piece of an etymology: plain text, the name of a language or a date.
+#[derive(Debug, PartialEq)]
+pub enum Span<'a> {
+    Text(&'a str),
+    Language(&'a str),
+    Date(&'a str),
+}
+
+/// Etymology of `word` from the first source of `chain` that has one, trying its lemma when the
+/// word itself has none. Sources that fail are skipped; the first failure is returned when no
+/// other source has an etymology.
+pub async fn etymology(word: &str, chain: &[Source]) -> Result<Etymology, DictionaryError> {
+    let term = normalize_term(word)?;
+    let lemma = Lemmatizer::global().lemmatize(&term).map(|lemma| lemma.lemma).filter(|lemma| *lemma != term);
+    let mut failure = None;
+
+    for &source in chain.iter().filter(|source| ETYMOLOGY_SOURCES.contains(source)) {
+        for candidate in iter::once(&term).chain(lemma.as_ref()) {
+            match definitions(source, candidate).await {
+                Ok(defs) => {
+                    if let Some(text) = defs.into_iter().find_map(|definition| definition.origin) {
+                        return Ok(Etymology { word: candidate.clone(), source, text });
+                    }
+                }
+                Err(e) if e.kind == DictionaryErrorKind::NotFound => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+    }
+
+    Err(failure.unwrap_or(DictionaryError {
+        kind: DictionaryErrorKind::NotFound,
+        message: "".to_string(),
+    }))
+}
+
+/// Known language names, longest first so that "Old English" wins over "English".
+fn languages() -> &'static [&'static str] {
+    static NAMES: OnceLock<Vec<&'static str>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        let mut names: Vec<&str> = LANGUAGES.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        names
+    })
+}
+
+/// Whether `text` has no letter or digit at `index`, so that a word can end there.
+fn ends_word(text: &str, index: usize) -> bool {
+    !text[index..].chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// Length of the language name `text` starts with.
+fn match_language(text: &str) -> Option<usize> {
+    languages().iter()
+        .find(|name| text.starts_with(*name) && ends_word(text, name.len()))
+        .map(|name| name.len())
+}
+
+/// Length of the date `text` starts with: a year such as "1590", a decade such as "1590s" or a
+/// century such as "14th century" or "15c.", with an optional qualifier such as "late".
+fn match_date(text: &str) -> Option<usize> {
+    let qualifier = DATE_QUALIFIERS.iter().find(|qualifier| text.starts_with(*qualifier)).map_or(0, |qualifier| qualifier.len());
+    let rest = &text[qualifier..];
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    let after = &rest[digits..];
+
+    let length = if digits == 0 {
+        return None;
+    } else if digits <= 2 && ["st", "nd", "rd", "th"].iter().any(|suffix| after.starts_with(suffix)) {
+        let suffix = &after[2..];
+        if suffix.starts_with(" century") {
+            digits + 2 + " century".len()
+        } else if suffix.starts_with(" c.") {
+            digits + 2 + " c.".len()
+        } else {
+            return None;
+        }
+    } else if digits <= 2 && after.starts_with("c.") {
+        digits + "c.".len()
+    } else if (3..=4).contains(&digits) {
+        digits + usize::from(after.starts_with('s'))
+    } else {
+        return None;
+    };
+
+    let end = qualifier + length;
+    (text[..end].ends_with('.') || ends_word(text, end)).then_some(end)
+}
+
+/// Splits etymology text into plain text, language names and dates.
+pub fn highlight(text: &str) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+
+    while index < text.len() {
+        let starts_word = !text[..index].chars().next_back().is_some_and(char::is_alphanumeric);
+        let rest = &text[index..];
+        let found = if starts_word {
+            match_language(rest).map(|length| (length, Span::Language(&rest[..length])))
+                .or_else(|| match_date(rest).map(|length| (length, Span::Date(&rest[..length]))))
+        } else {
+            None
+        };
+
+        match found {
+            Some((length, span)) => {
+                if index > text_start {
+                    spans.push(Span::Text(&text[text_start..index]));
+                }
+                spans.push(span);
+                index += length;
+                text_start = index;
+            }
+            None => index += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    if text_start < text.len() {
+        spans.push(Span::Text(&text[text_start..]));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_languages_and_dates() {
+        let spans = highlight("From Middle English dogge (late 14th century), from Old English docga, c. 1050; Englishman");
+        assert_eq!(spans, vec![
+            Span::Text("From "),
+            Span::Language("Middle English"),
+            Span::Text(" dogge ("),
+            Span::Date("late 14th century"),
+            Span::Text("), from "),
+            Span::Language("Old English"),
+            Span::Text(" docga, "),
+            Span::Date("c. 1050"),
+            Span::Text("; Englishman"),
+        ]);
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(match_date("1590s, from"), Some(5));
+        assert_eq!(match_date("15c. word"), Some(4));
+        assert_eq!(match_date("12th c. Latin"), Some(7));
+        assert_eq!(match_date("12 of them"), None);
+        assert_eq!(match_date("1590ish"), None);
+        assert_eq!(match_date("late Latin"), None);
+    }
+}
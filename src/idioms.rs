@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use log::warn;
+
+use crate::dictionary::{Definition, DefinitionDetail, DictionaryError, DictionaryErrorKind, Meaning};
+use crate::lemma::Lemmatizer;
+use crate::sentence::tokenize;
+
+const BUNDLED_IDIOMS: &str = include_str!("idioms/idioms.tsv");
+
+/// Particles that follow the verb of a phrasal verb, as in "give up" or "look after".
+const PARTICLES: [&str; 16] = [
+    "about", "across", "after", "along", "around", "away", "back", "by", "down", "in", "into", "off", "on", "out",
+    "over", "up",
+];
+
+/// Small words that make a phrase more than a compound noun, as in "kick the bucket".
+const FUNCTION_WORDS: [&str; 14] = ["a", "an", "the", "of", "in", "on", "to", "and", "or", "with", "is", "your", "it", "someone's"];
+
+/// What kind of multi-word expression a phrase is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhraseKind {
+    Idiom,
+    PhrasalVerb,
+    Compound,
+}
+
+impl PhraseKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            PhraseKind::Idiom => "idiom",
+            PhraseKind::PhrasalVerb => "phrasal verb",
+            PhraseKind::Compound => "compound",
+        }
+    }
+
+    /// Kind of `phrase`: as listed in the bundled dataset, otherwise guessed from its words.
+    /// Single words are not phrases.
+    pub fn of(phrase: &str) -> Option<PhraseKind> {
+        if let Some(idiom) = Idioms::global().get(phrase) {
+            return Some(idiom.kind);
+        }
+        let words = tokenize(phrase);
+        match words.as_slice() {
+            [] | [_] => None,
+            [_, particle] if PARTICLES.contains(&particle.as_str()) => Some(PhraseKind::PhrasalVerb),
+            _ if words.iter().any(|word| FUNCTION_WORDS.contains(&word.as_str())) => Some(PhraseKind::Idiom),
+            _ => Some(PhraseKind::Compound),
+        }
+    }
+}
+
+impl FromStr for PhraseKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "idiom" => Ok(PhraseKind::Idiom),
+            "phrasal verb" => Ok(PhraseKind::PhrasalVerb),
+            "compound" => Ok(PhraseKind::Compound),
+            other => Err(format!("Unknown phrase kind: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Idiom {
+    pub phrase: String,
+    pub kind: PhraseKind,
+    pub definition: String,
+    pub example: Option<String>,
+}
+
+/// Bundled dataset of idioms, phrasal verbs and compounds.
+#[derive(Debug, Default)]
+pub struct Idioms {
+    entries: HashMap<String, Idiom>,
+}
+
+impl Idioms {
+    /// Reads lines of `phrase<TAB>kind<TAB>definition<TAB>example`, skipping `#` comments.
+    pub fn parse(source: &str) -> Self {
+        let mut idioms = Idioms::default();
+        for (number, line) in source.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let idiom = match fields[..] {
+                [phrase, kind, definition, ..] => kind.parse().map(|kind| Idiom {
+                    phrase: phrase.to_string(),
+                    kind,
+                    definition: definition.to_string(),
+                    example: fields.get(3).filter(|example| !example.is_empty()).map(|example| example.to_string()),
+                }),
+                _ => Err("missing fields".to_string()),
+            };
+            match idiom {
+                Ok(idiom) => {
+                    idioms.entries.insert(tokenize(&idiom.phrase).join(" "), idiom);
+                }
+                Err(err) => warn!("Skipping idiom line {}: {}", number + 1, err),
+            }
+        }
+        idioms
+    }
+
+    pub fn global() -> &'static Idioms {
+        static IDIOMS: OnceLock<Idioms> = OnceLock::new();
+        IDIOMS.get_or_init(|| Idioms::parse(BUNDLED_IDIOMS))
+    }
+
+    /// Entry for `phrase`, also found when its first word is inflected, as in "kicked the bucket".
+    pub fn get(&self, phrase: &str) -> Option<&Idiom> {
+        let mut words = tokenize(phrase);
+        if let Some(idiom) = self.entries.get(&words.join(" ")) {
+            return Some(idiom);
+        }
+        let lemma = Lemmatizer::global().lemmatize(words.first()?)?;
+        words[0] = lemma.lemma;
+        self.entries.get(&words.join(" "))
+    }
+
+    /// Every entry, in no particular order.
+    pub fn all(&self) -> impl Iterator<Item = &Idiom> {
+        self.entries.values()
+    }
+
+    pub fn contains(&self, phrase: &str) -> bool {
+        self.get(phrase).is_some()
+    }
+
+    /// Entry for `phrase` shaped like a dictionary entry, with the phrase kind as part of speech.
+    pub fn definition(&self, phrase: &str) -> Result<Vec<Definition>, DictionaryError> {
+        match self.get(phrase) {
+            Some(idiom) => Ok(vec![Definition {
+                word: idiom.phrase.clone(),
+                meanings: vec![Meaning {
+                    part_of_speech: idiom.kind.label().to_string(),
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                    definitions: vec![DefinitionDetail {
+                        definition: idiom.definition.clone(),
+                        antonyms: None,
+                        synonyms: None,
+                        example: idiom.example.clone(),
+                        hypernyms: Vec::new(),
+                        hyponyms: Vec::new(),
+                    }],
+                }],
+                origin: None,
+            }]),
+            None => Err(DictionaryError {
+                kind: DictionaryErrorKind::NotFound,
+                message: "".to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_dataset_parses() {
+        let idioms = Idioms::parse(BUNDLED_IDIOMS);
+        assert!(idioms.entries.len() > 50);
+        assert_eq!(idioms.get("Break a leg!").map(|idiom| idiom.kind), Some(PhraseKind::Idiom));
+        assert_eq!(idioms.get("gave up").map(|idiom| idiom.phrase.as_str()), Some("give up"));
+        assert_eq!(idioms.get("kicked the bucket").map(|idiom| idiom.phrase.as_str()), Some("kick the bucket"));
+    }
+
+    #[test]
+    fn test_definition_uses_kind_as_part_of_speech() {
+        let definitions = Idioms::global().definition("ice cream").unwrap();
+        assert_eq!(definitions[0].meanings[0].part_of_speech, "compound");
+        assert_eq!(Idioms::global().definition("green cheese").unwrap_err().kind, DictionaryErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_guess_phrase_kind() {
+        assert_eq!(PhraseKind::of("hello"), None);
+        assert_eq!(PhraseKind::of("piece of cake"), Some(PhraseKind::Idiom));
+        assert_eq!(PhraseKind::of("log in"), Some(PhraseKind::PhrasalVerb));
+        assert_eq!(PhraseKind::of("jump over the moon"), Some(PhraseKind::Idiom));
+        assert_eq!(PhraseKind::of("coffee table"), Some(PhraseKind::Compound));
+    }
+}
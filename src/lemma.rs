@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::words::WordList;
+
+const IRREGULAR_FORMS: &str = include_str!("lemma/irregular.txt");
+
+/// Suffixes are only removed when this much of the word is left, so "as" or "red" keep theirs.
+const MIN_LEMMA_LENGTH: usize = 2;
+
+/// Suffix rules: inflected ending, what replaces it, and the form it marks. The first matching
+/// rule wins, so longer endings come before the endings they contain.
+const RULES: [(&str, &str, &str); 12] = [
+    ("ies", "y", "plural or 3rd person singular"),
+    ("ied", "y", "past tense"),
+    ("ying", "ie", "present participle"),
+    ("iest", "y", "superlative"),
+    ("ier", "y", "comparative"),
+    ("sses", "ss", "plural or 3rd person singular"),
+    ("es", "", "plural or 3rd person singular"),
+    ("s", "", "plural or 3rd person singular"),
+    ("ing", "", "present participle"),
+    ("ed", "", "past tense"),
+    ("est", "", "superlative"),
+    ("er", "", "comparative"),
+];
+
+/// Headword an inflected form was reduced to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lemma {
+    pub lemma: String,
+    /// Grammatical form of the original word, such as "present participle".
+    pub form: String,
+}
+
+/// Reduces inflected English words to their headword with a table of irregular forms and
+/// suffix rules checked against the shared [`WordList`].
+#[derive(Debug, Default)]
+pub struct Lemmatizer {
+    irregular: HashMap<String, Lemma>,
+}
+
+impl Lemmatizer {
+    /// Reads lines of `form<TAB>lemma<TAB>grammatical form`; the first lemma of a form is kept.
+    pub fn new(irregular_forms: &str) -> Self {
+        let mut lemmatizer = Lemmatizer::default();
+        for line in irregular_forms.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+            if let [form, lemma, grammatical_form] = line.split('\t').collect::<Vec<_>>()[..] {
+                lemmatizer.irregular.entry(form.to_string()).or_insert_with(|| Lemma {
+                    lemma: lemma.to_string(),
+                    form: grammatical_form.to_string(),
+                });
+            }
+        }
+        lemmatizer
+    }
+
+    pub fn global() -> &'static Lemmatizer {
+        static LEMMATIZER: OnceLock<Lemmatizer> = OnceLock::new();
+        LEMMATIZER.get_or_init(|| Lemmatizer::new(IRREGULAR_FORMS))
+    }
+
+    /// Headword of `word`, or `None` when it does not look inflected.
+    pub fn lemmatize(&self, word: &str) -> Option<Lemma> {
+        self.lemmatize_with(word, |candidate| WordList::global().contains(candidate))
+    }
+
+    /// Like [`Lemmatizer::lemmatize`], preferring the candidate stems `is_word` accepts.
+    fn lemmatize_with(&self, word: &str, is_word: impl Fn(&str) -> bool) -> Option<Lemma> {
+        let word = word.trim().to_lowercase();
+        if let Some(lemma) = self.irregular.get(&word) {
+            return Some(lemma.clone());
+        }
+        if !word.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let (suffix, stem, replacement, form) = RULES.iter().find_map(|(suffix, replacement, form)| {
+            word.strip_suffix(suffix)
+                .filter(|stem| stem.len() + replacement.len() >= MIN_LEMMA_LENGTH && applies(suffix, stem))
+                .map(|stem| (*suffix, stem, *replacement, *form))
+        })?;
+
+        let (candidates, fallback) = candidates(suffix, stem, replacement);
+        let lemma = candidates.into_iter().find(|candidate| is_word(candidate)).unwrap_or(fallback);
+        Some(Lemma { lemma, form: form.to_string() })
+    }
+}
+
+/// Whether removing `suffix` from a word leaving `stem` is plausible: "es" only follows
+/// sibilants and "o", and a plural "s" never follows "s" or "u", as in "glass" or "bus".
+fn applies(suffix: &str, stem: &str) -> bool {
+    match suffix {
+        "es" => ["s", "x", "z", "ch", "sh", "o"].iter().any(|ending| stem.ends_with(ending)),
+        "s" => !stem.ends_with('s') && !stem.ends_with('u'),
+        _ => true,
+    }
+}
+
+/// Possible headwords once `suffix` is removed, in the order they are checked against the word
+/// list, and the one used when none is listed. Before a vowel suffix a doubled consonant is
+/// undone ("running") and a stem ending in vowel and consonant most likely lost a silent "e"
+/// ("hoped").
+fn candidates(suffix: &str, stem: &str, replacement: &str) -> (Vec<String>, String) {
+    let bare = format!("{}{}", stem, replacement);
+    if !replacement.is_empty() || !["ing", "ed", "er", "est"].contains(&suffix) {
+        return (vec![bare.clone()], bare);
+    }
+
+    let bytes = stem.as_bytes();
+    let is_vowel = |byte: u8| b"aeiou".contains(&byte);
+    let last = bytes[bytes.len() - 1];
+    let before_last = bytes[bytes.len() - 2];
+    if last == before_last && !is_vowel(last) {
+        let undoubled = stem[..stem.len() - 1].to_string();
+        return (vec![undoubled.clone(), bare], undoubled);
+    }
+    let with_e = format!("{}e", stem);
+    if !is_vowel(last) && is_vowel(before_last) {
+        (vec![with_e, bare.clone()], bare)
+    } else {
+        (vec![bare.clone(), with_e], bare)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lemma(word: &str) -> Option<(String, String)> {
+        let listed = ["run", "make", "stop", "box", "city", "fall", "nice", "big", "hope", "hop", "die"];
+        Lemmatizer::new(IRREGULAR_FORMS)
+            .lemmatize_with(word, |candidate| listed.contains(&candidate))
+            .map(|lemma| (lemma.lemma, lemma.form))
+    }
+
+    fn expect(lemma: &str, form: &str) -> Option<(String, String)> {
+        Some((lemma.to_string(), form.to_string()))
+    }
+
+    #[test]
+    fn test_irregular_forms() {
+        assert_eq!(lemma("mice"), expect("mouse", "plural"));
+        assert_eq!(lemma("Better"), expect("good", "comparative"));
+        assert_eq!(lemma("went"), expect("go", "past tense"));
+    }
+
+    #[test]
+    fn test_suffix_rules() {
+        assert_eq!(lemma("running"), expect("run", "present participle"));
+        assert_eq!(lemma("making"), expect("make", "present participle"));
+        assert_eq!(lemma("falling"), expect("fall", "present participle"));
+        assert_eq!(lemma("stopped"), expect("stop", "past tense"));
+        assert_eq!(lemma("hoped"), expect("hope", "past tense"));
+        assert_eq!(lemma("hopping"), expect("hop", "present participle"));
+        assert_eq!(lemma("cities"), expect("city", "plural or 3rd person singular"));
+        assert_eq!(lemma("boxes"), expect("box", "plural or 3rd person singular"));
+        assert_eq!(lemma("nicer"), expect("nice", "comparative"));
+        assert_eq!(lemma("biggest"), expect("big", "superlative"));
+        assert_eq!(lemma("dying"), expect("die", "present participle"));
+    }
+
+    #[test]
+    fn test_uninflected_words() {
+        assert_eq!(lemma("glass"), None);
+        assert_eq!(lemma("is"), expect("be", "present tense"));
+        assert_eq!(lemma("bus"), None);
+        assert_eq!(lemma("rock'n'roll"), None);
+    }
+}
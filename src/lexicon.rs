@@ -0,0 +1,171 @@
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+use crate::dictionary::{DictionaryError, DictionaryErrorKind};
+use crate::scores;
+use crate::words::WordList;
+
+const BUNDLED_LEXICONS: [(&str, &str); 2] = [
+    ("enable", include_str!("lexicon/enable.txt")),
+    ("collins", include_str!("lexicon/collins.txt")),
+];
+
+/// Official word list of word games, such as ENABLE.
+#[derive(Debug)]
+pub struct Lexicon {
+    pub name: String,
+    /// Whether only part of the list is known, so that a missing word may still be allowed.
+    pub excerpt: bool,
+    words: WordList,
+}
+
+/// Word lists a word can be checked against.
+#[derive(Debug, Default)]
+pub struct Lexicons {
+    lists: Vec<Lexicon>,
+}
+
+/// Whether a word list allows a word.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Valid,
+    NotValid,
+    /// Missing from a bundled excerpt, which says nothing about the full list.
+    NotInExcerpt,
+}
+
+/// Whether a word is allowed by each word list checked, with its scores.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validity {
+    pub word: String,
+    pub lists: Vec<(String, Verdict)>,
+    pub scrabble: u32,
+    pub words_with_friends: u32,
+}
+
+impl Lexicon {
+    fn verdict(&self, word: &str) -> Verdict {
+        if self.words.contains(word) {
+            Verdict::Valid
+        } else if self.excerpt {
+            Verdict::NotInExcerpt
+        } else {
+            Verdict::NotValid
+        }
+    }
+}
+
+impl Lexicons {
+    /// Adds a list of one word per line, or only part of it when `excerpt` is set, replacing any
+    /// list of the same name.
+    pub fn add(&mut self, name: &str, source: &str, excerpt: bool) {
+        let lexicon = Lexicon { name: name.to_lowercase(), excerpt, words: WordList::new(source.lines()) };
+        match self.lists.iter_mut().find(|list| list.name == lexicon.name) {
+            Some(list) => *list = lexicon,
+            None => self.lists.push(lexicon),
+        }
+    }
+
+    /// Bundled excerpts, replaced or extended by the lists named in `WORDBOT_LEXICONS` as
+    /// comma separated `name=path` pairs.
+    pub fn global() -> &'static Lexicons {
+        static LEXICONS: OnceLock<Lexicons> = OnceLock::new();
+        LEXICONS.get_or_init(|| {
+            let mut lexicons = Lexicons::default();
+            for (name, source) in BUNDLED_LEXICONS {
+                lexicons.add(name, source, true);
+            }
+            let configured = env::var("WORDBOT_LEXICONS").unwrap_or_default();
+            for entry in configured.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+                let Some((name, path)) = entry.split_once('=') else {
+                    warn!("Ignoring word list {} without a name", entry);
+                    continue;
+                };
+                match fs::read_to_string(path.trim()) {
+                    Ok(source) => {
+                        info!("Loaded word list {} from {}", name.trim(), path.trim());
+                        lexicons.add(name.trim(), &source, false);
+                    }
+                    Err(err) => warn!("Failed to read word list {}: {}", path.trim(), err),
+                }
+            }
+            lexicons
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.lists.iter().map(|list| list.name.as_str()).collect()
+    }
+
+    /// Checks `word` against the lists named in `names`, or against all of them when there are
+    /// none.
+    pub fn check(&self, word: &str, names: &[&str]) -> Result<Validity, DictionaryError> {
+        let mut lists = Vec::new();
+        for name in names {
+            match self.lists.iter().find(|list| list.name.eq_ignore_ascii_case(name)) {
+                Some(list) => lists.push(list),
+                None => {
+                    return Err(DictionaryError {
+                        kind: DictionaryErrorKind::InvalidInput,
+                        message: format!("Unknown word list {}, try one of: {}", name, self.names().join(", ")),
+                    });
+                }
+            }
+        }
+        if names.is_empty() {
+            lists = self.lists.iter().collect();
+        }
+
+        let word = word.trim().to_lowercase();
+        Ok(Validity {
+            lists: lists.iter().map(|list| (list.name.clone(), list.verdict(&word))).collect(),
+            scrabble: scores::scrabble(&word),
+            words_with_friends: scores::words_with_friends(&word),
+            word,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexicons() -> Lexicons {
+        let mut lexicons = Lexicons::default();
+        for (name, source) in BUNDLED_LEXICONS {
+            lexicons.add(name, source, true);
+        }
+        lexicons
+    }
+
+    #[test]
+    fn test_check_against_each_list() {
+        let validity = lexicons().check(" QI ", &[]).unwrap();
+        assert_eq!(validity.word, "qi");
+        assert_eq!(validity.lists, vec![("enable".to_string(), Verdict::NotInExcerpt), ("collins".to_string(), Verdict::Valid)]);
+        assert_eq!((validity.scrabble, validity.words_with_friends), (11, 11));
+
+        let validity = lexicons().check("house", &["ENABLE"]).unwrap();
+        assert_eq!(validity.lists, vec![("enable".to_string(), Verdict::Valid)]);
+    }
+
+    #[test]
+    fn test_unknown_list() {
+        let error = lexicons().check("qi", &["sowpods"]).unwrap_err();
+        assert_eq!(error.kind, DictionaryErrorKind::InvalidInput);
+        assert!(error.message.ends_with("enable, collins"));
+    }
+
+    #[test]
+    fn test_add_replaces_list_of_same_name() {
+        let mut lexicons = lexicons();
+        lexicons.add("Enable", "qi\n", false);
+        assert_eq!(lexicons.names(), ["enable", "collins"]);
+        let validity = lexicons.check("za", &["enable", "collins"]).unwrap();
+        assert_eq!(validity.lists, vec![("enable".to_string(), Verdict::NotValid), ("collins".to_string(), Verdict::Valid)]);
+        assert_eq!(lexicons.check("qi", &["enable"]).unwrap().lists[0].1, Verdict::Valid);
+    }
+}
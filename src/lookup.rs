@@ -0,0 +1,283 @@
+use std::env;
+use std::str::FromStr;
+
+use log::warn;
+
+use crate::dictionary::glossary::Glossary;
+use crate::dictionary::wiktionary::Wiktionary;
+use crate::dictionary::wordnet::WordNet;
+use crate::dictionary::{self, Definition, DictionaryError, DictionaryErrorKind};
+use crate::idioms::{Idioms, PhraseKind};
+use crate::lemma::{Lemma, Lemmatizer};
+use crate::normalize::normalize_term;
+use crate::settings::UrbanFilter;
+use crate::urban::{self, UrbanDefinition};
+
+/// Sources tried in order when a word is looked up without choosing one.
+pub const DEFAULT_CHAIN: [Source; 6] = [
+    Source::Dictionary, Source::WordNet, Source::Wiktionary, Source::Glossary, Source::Idioms, Source::Urban,
+];
+
+/// Place a definition can come from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Dictionary,
+    /// Merriam-Webster API, tried only when listed in the chain as it needs a key.
+    MerriamWebster,
+    /// Local WordNet database, answering without the network.
+    WordNet,
+    /// Local index built from a Wiktionary extract.
+    Wiktionary,
+    Glossary,
+    /// Bundled idioms, phrasal verbs and compounds.
+    Idioms,
+    Urban,
+}
+
+impl Source {
+    /// Name shown to users when this source answered in place of another one.
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::Dictionary => "Free Dictionary",
+            Source::MerriamWebster => "Merriam-Webster",
+            Source::WordNet => "WordNet",
+            Source::Wiktionary => "Wiktionary",
+            Source::Glossary => "the glossary",
+            Source::Idioms => "the idiom collection",
+            Source::Urban => "Urban Dictionary",
+        }
+    }
+
+    /// Whether the source can answer at all: the APIs need their key and the local databases need
+    /// to have been loaded.
+    pub fn is_configured(self) -> bool {
+        match self {
+            Source::MerriamWebster => has_key("MERRIAM_WEBSTER_KEY"),
+            Source::Urban => has_key("URBAN_DICTIONARY_KEY"),
+            Source::WordNet => !WordNet::global().is_empty(),
+            Source::Wiktionary => !Wiktionary::global().is_empty(),
+            Source::Dictionary | Source::Glossary | Source::Idioms => true,
+        }
+    }
+
+    /// Whether not knowing a word means it is likely not a word at all, rather than only missing
+    /// from a small collection such as the glossary.
+    pub fn is_comprehensive(self) -> bool {
+        !matches!(self, Source::Glossary | Source::Idioms)
+    }
+}
+
+fn has_key(name: &str) -> bool {
+    env::var(name).is_ok_and(|key| !key.is_empty())
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "dictionary" | "free" => Ok(Source::Dictionary),
+            "merriam-webster" | "mw" => Ok(Source::MerriamWebster),
+            "wordnet" => Ok(Source::WordNet),
+            "wiktionary" => Ok(Source::Wiktionary),
+            "glossary" => Ok(Source::Glossary),
+            "idioms" | "idiom" => Ok(Source::Idioms),
+            "urban" => Ok(Source::Urban),
+            other => Err(format!("Unknown source: {}", other)),
+        }
+    }
+}
+
+/// Parses a comma separated list of sources, keeping the first mention of each one.
+pub fn parse_chain(value: &str) -> Result<Vec<Source>, String> {
+    let mut chain = Vec::new();
+    for name in value.split(',').filter(|name| !name.trim().is_empty()) {
+        let source = name.parse::<Source>()?;
+        if !chain.contains(&source) {
+            chain.push(source);
+        }
+    }
+    if chain.is_empty() {
+        return Err("The fallback chain is empty".to_string());
+    }
+    Ok(chain)
+}
+
+/// Definitions found by one of the sources.
+pub enum Answer {
+    Definitions(Vec<Definition>),
+    Urban(Vec<UrbanDefinition>),
+}
+
+/// Successful lookup: the normalized term, the source that answered and, when only the headword
+/// of an inflected form was known, that headword.
+pub struct Found {
+    pub term: String,
+    pub source: Source,
+    pub answer: Answer,
+    pub lemma: Option<Lemma>,
+    /// Kind of expression when the term has several words.
+    pub phrase_kind: Option<PhraseKind>,
+}
+
+/// Sources that did not know a word: whether any comprehensive one answered that it does not know
+/// it, and the first one that failed instead.
+#[derive(Default)]
+struct Misses {
+    not_found: bool,
+    failure: Option<DictionaryError>,
+}
+
+impl Misses {
+    /// Keeps the answer of `source`, recording it when it is a miss.
+    fn record(&mut self, source: Source, word: &str, result: Result<Option<Answer>, DictionaryError>) -> Option<Answer> {
+        match result {
+            Ok(Some(answer)) => return Some(answer),
+            Ok(None) => self.not_found |= source.is_comprehensive(),
+            Err(e) => {
+                warn!("{} failed for {:?}: {}", source.label(), word, e.message);
+                self.failure.get_or_insert(e);
+            }
+        }
+        None
+    }
+
+    /// Error of a lookup nobody answered: not found as soon as a comprehensive source said so, so
+    /// that a source being down does not hide the suggestions shown for unknown words, and the
+    /// failure otherwise.
+    fn into_error(self) -> DictionaryError {
+        match self.failure {
+            Some(failure) if !self.not_found => failure,
+            _ => DictionaryError {
+                kind: DictionaryErrorKind::NotFound,
+                message: "".to_string(),
+            },
+        }
+    }
+}
+
+/// Looks `word` up in every source of `chain` until one knows it, asking each source for the word
+/// as normalized and then for its lemma. A source that fails is skipped so that the next ones can
+/// stand in for it; its error is returned unless a comprehensive source answered that it does not
+/// know the word. Sources that are not configured are skipped, and Urban definitions hidden by
+/// `filter` count as a miss.
+pub async fn lookup(word: &str, chain: &[Source], filter: UrbanFilter) -> Result<Found, DictionaryError> {
+    let term = normalize_term(word)?;
+    let lemma = Lemmatizer::global().lemmatize(&term).filter(|lemma| lemma.lemma != term);
+    let mut misses = Misses::default();
+    for &source in chain {
+        if !source.is_configured() {
+            continue;
+        }
+        let result = lookup_source(source, &term, filter).await;
+        let failed = result.is_err();
+        if let Some(answer) = misses.record(source, &term, result) {
+            let phrase_kind = PhraseKind::of(&term);
+            return Ok(Found { term, source, answer, lemma: None, phrase_kind });
+        }
+        if failed {
+            continue;
+        }
+        if let Some(lemma) = &lemma {
+            if let Some(answer) = misses.record(source, &lemma.lemma, lookup_source(source, &lemma.lemma, filter).await) {
+                return Ok(Found { term, source, answer, lemma: Some(lemma.clone()), phrase_kind: None });
+            }
+        }
+    }
+
+    Err(misses.into_error())
+}
+
+/// Dictionary entries of `word` in `source`. Urban Dictionary has none, so it never knows the word.
+pub async fn definitions(source: Source, word: &str) -> Result<Vec<Definition>, DictionaryError> {
+    match source {
+        Source::Dictionary => dictionary::definition(word).await,
+        Source::MerriamWebster => dictionary::merriam_webster(word).await,
+        Source::WordNet => WordNet::global().definition(word),
+        Source::Wiktionary => {
+            // Entries are read from disk, which would hold up the other chats served by this thread.
+            let word = word.to_string();
+            tokio::task::spawn_blocking(move || Wiktionary::global().definition(&word)).await.unwrap_or_else(|err| {
+                Err(DictionaryError {
+                    kind: DictionaryErrorKind::Unknown,
+                    message: err.to_string(),
+                })
+            })
+        }
+        Source::Glossary => Glossary::global().definition(word),
+        Source::Idioms => Idioms::global().definition(word),
+        Source::Urban => Err(DictionaryError {
+            kind: DictionaryErrorKind::NotFound,
+            message: "".to_string(),
+        }),
+    }
+}
+
+/// Definitions of `word` in `source`, or `None` when it does not know the word.
+async fn lookup_source(source: Source, word: &str, filter: UrbanFilter) -> Result<Option<Answer>, DictionaryError> {
+    let answer = match source {
+        Source::Urban => urban::definition(word).await.map(|defs| Answer::Urban(filter.apply(defs))),
+        source => definitions(source, word).await.map(Answer::Definitions),
+    };
+    match answer {
+        Ok(Answer::Definitions(defs)) if defs.is_empty() => Ok(None),
+        Ok(Answer::Urban(defs)) if defs.is_empty() => Ok(None),
+        Ok(answer) => Ok(Some(answer)),
+        Err(e) if e.kind == DictionaryErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chain() {
+        assert_eq!(parse_chain("urban, dictionary"), Ok(vec![Source::Urban, Source::Dictionary]));
+        assert_eq!(parse_chain("Glossary,glossary,"), Ok(vec![Source::Glossary]));
+        assert_eq!(parse_chain("idioms,urban"), Ok(vec![Source::Idioms, Source::Urban]));
+        assert_eq!(parse_chain("wordnet,dictionary"), Ok(vec![Source::WordNet, Source::Dictionary]));
+        assert_eq!(parse_chain("mw,dictionary"), Ok(vec![Source::MerriamWebster, Source::Dictionary]));
+    }
+
+    fn failure() -> DictionaryError {
+        DictionaryError {
+            kind: DictionaryErrorKind::Unknown,
+            message: "Unknown server error occurred".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_not_found_wins_over_failing_source() {
+        let mut misses = Misses::default();
+        assert!(misses.record(Source::Dictionary, "qwzx", Err(failure())).is_none());
+        assert!(misses.record(Source::WordNet, "qwzx", Ok(None)).is_none());
+        assert_eq!(misses.into_error().kind, DictionaryErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_local_misses_do_not_hide_remote_failure() {
+        let mut misses = Misses::default();
+        misses.record(Source::Dictionary, "dog", Err(failure()));
+        misses.record(Source::Glossary, "dog", Ok(None));
+        misses.record(Source::Idioms, "dog", Ok(None));
+        let error = misses.into_error();
+        assert_eq!(error.kind, DictionaryErrorKind::Unknown);
+        assert_eq!(error.message, failure().message);
+    }
+
+    #[test]
+    fn test_failure_returned_when_every_source_failed() {
+        let mut misses = Misses::default();
+        misses.record(Source::Dictionary, "dog", Err(failure()));
+        assert_eq!(misses.into_error().kind, DictionaryErrorKind::Unknown);
+        assert_eq!(Misses::default().into_error().kind, DictionaryErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_parse_chain_rejects_unknown_or_empty() {
+        assert!(parse_chain("dictionary,wikipedia").is_err());
+        assert!(parse_chain(" , ").is_err());
+    }
+}
@@ -1,18 +1,54 @@
+use std::env;
 use std::error::Error;
+use std::path::Path;
 use std::time::Duration;
 use log::{error, info, LevelFilter};
 use pretty_env_logger::formatted_builder;
 use teloxide::{prelude::*, utils::command::BotCommands};
-use teloxide::types::{Me, ParseMode};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, Me};
 use tokio::time;
 use telegram::Telegram;
 
-use crate::dictionary::{DictionaryErrorKind};
-use crate::telegram::LogFormat;
+use crate::anagram::AnagramIndex;
+use crate::config::Config;
+use crate::dictionary::wiktionary::{self, Wiktionary};
+use crate::dictionary::wordnet::WordNet;
+use crate::dictionary::{DictionaryError, DictionaryErrorKind};
+use crate::discovery::WordGroups;
+use crate::lexicon::Lexicons;
+use crate::lookup::{Answer, Found, Source};
+use crate::metrics::FrequencyList;
+use crate::pattern::{Matches, PatternIndex, Query};
+use crate::pronunciation::Pronunciations;
+use crate::reverse::{ReverseIndex, MAX_CANDIDATES};
+use crate::settings::{Settings, UrbanFilter};
+use crate::suggest::{Suggester, MAX_SUGGESTIONS};
+use crate::telegram::{split_message, Document, Format, LogFormat, RenderOptions, Reply, StartPayload, MAX_MESSAGE_LENGTH};
+use crate::thesaurus::{Relation, Thesaurus};
+use crate::words::WordList;
 
+mod anagram;
+mod config;
 mod dictionary;
+mod discovery;
+mod etymology;
+mod idioms;
+mod lemma;
+mod lexicon;
+mod lookup;
+mod metrics;
+mod normalize;
+mod pattern;
+mod pronunciation;
+mod reverse;
+mod scores;
+mod sentence;
+mod settings;
+mod suggest;
 mod telegram;
+mod thesaurus;
 mod urban;
+mod words;
 
 #[tokio::main]
 async fn main() {
@@ -21,9 +57,29 @@ async fn main() {
         .default_format()
         .init();
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import-wiktionary") {
+        match &args[1..] {
+            [input, output] => match wiktionary::import(Path::new(input), Path::new(output)) {
+                Ok(count) => info!("Imported {} Wiktionary entries into {}", count, output),
+                Err(err) => error!("Failed to import {}: {}", input, err),
+            },
+            _ => error!("Usage: wordbot import-wiktionary <extract.jsonl> <index directory>"),
+        }
+        return;
+    }
+
     info!("Starting word bot...");
 
     let bot = Bot::from_env();
+    let config = Config::from_env();
+    let settings = Settings::default();
+    // Built before the first lookup, /anagram or /level rather than while answering it.
+    Suggester::global();
+    WordNet::global();
+    Wiktionary::global();
+    AnagramIndex::global();
+    FrequencyList::global();
 
     let mut commands_set = false;
     while !commands_set {
@@ -40,46 +96,225 @@ async fn main() {
     }
 
     let handler = dptree::entry()
-        .branch(Update::filter_message().endpoint(message_handler));
-    // .branch(Update::filter_callback_query().endpoint(callback_handler))
+        .branch(Update::filter_message().endpoint(message_handler))
+        .branch(Update::filter_callback_query().endpoint(callback_handler));
     // .branch(Update::filter_inline_query().endpoint(inline_query_handler));
 
-    Dispatcher::builder(bot, handler).enable_ctrlc_handler().build().dispatch().await;
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![config, settings])
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
 }
 
 #[derive(BotCommands, Clone, Debug)]
-#[command(rename_rule = "lowercase", description = "These commands are supported\\. Without command I will send you the definition of the text that you sent me")]
+#[command(rename_rule = "lowercase", description = "These commands are supported. Without command I will send you the definition of the text that you sent me")]
 enum Command {
-    #[command(description = "Display this text\\.")]
+    #[command(description = "Start the bot.")]
+    Start(String),
+    #[command(description = "Display this text.")]
     Help,
-    #[command(description = "Seek the text in the dictionary\\.")]
+    #[command(description = "Seek the text in the dictionary, falling back to other sources.")]
     Info(String),
-    #[command(description = "Seek the text in the urban dictionary\\.")]
+    #[command(description = "List synonyms of a word.")]
+    Syn(String),
+    #[command(description = "List antonyms of a word.")]
+    Ant(String),
+    #[command(description = "List words rhyming with a word.")]
+    Rhyme(String),
+    #[command(description = "List words sounding like a word.")]
+    SoundsLike(String),
+    #[command(description = "List words related to a word.")]
+    Related(String),
+    #[command(description = "Find words matching a description.")]
+    WhatWord(String),
+    #[command(description = "Find words spelled like a pattern, where ? is one letter and * any letters, as in \"c?t* 3-5\".")]
+    Pattern(String),
+    #[command(description = "Find crossword answers from known letters and a length, as in \"_a_e 4\".")]
+    Crossword(String),
+    #[command(description = "List the words made of some or all of the given letters.")]
+    Anagram(String),
+    #[command(description = "Check a word against game word lists, or only those named after it, and score it.")]
+    Valid(String),
+    #[command(description = "Tell how common a word is and the level learners usually know it at.")]
+    Level(String),
+    #[command(description = "Tell where a word comes from.")]
+    Origin(String),
+    #[command(description = "Translate a word, into one language when named last, as in \"dog french\".")]
+    Translate(String),
+    #[command(description = "Seek the text in the urban dictionary.")]
     Urban(String),
+    #[command(description = "Show a random urban definition.")]
+    UrbanRandom,
+    #[command(description = "Show the urban word of the day.")]
+    UrbanOfTheDay,
+    #[command(description = "Collapse senses after the first when a part of speech has more than N (0 disables).")]
+    Collapse(String),
+    #[command(description = "Show syllables, frequency and level with definitions: on or off.")]
+    Metrics(String),
+    #[command(description = "Order urban definitions by upstream, votes or newest.")]
+    UrbanSort(String),
+    #[command(description = "Hide urban definitions with fewer net votes than N (off disables).")]
+    UrbanMinScore(String),
+    #[command(description = "Choose what to do with explicit urban content: allow, mask or hide.")]
+    Nsfw(String),
 }
 
 async fn message_handler(
     bot: Bot,
     msg: Message,
     me: Me,
+    config: Config,
+    settings: Settings,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     if let Some(text) = msg.text() {
         info!("Received message: {}", msg.format_for_log());
+        let chat_settings = settings.get(msg.chat.id);
+        let options = chat_settings.render_options(me.username(), msg.chat.is_private());
         match BotCommands::parse(text, me.username()) {
+            Ok(Command::Start(payload)) => {
+                let response = match StartPayload::decode(payload.trim()) {
+                    Some(StartPayload::Urban(term)) => build_urban_response(&term, chat_settings.urban_filter(msg.chat.is_private()), &options).await.into(),
+                    Some(StartPayload::Info(term)) => build_info_response(&term, &config.fallback_chain, chat_settings.urban_filter(msg.chat.is_private()), &options).await,
+                    None => build_help_response().into(),
+                };
+                send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
+            }
             Ok(Command::Help) => {
-                send_large_message(bot.clone(), msg.chat.id, Command::descriptions().to_string()).await?;
+                send_large_message(bot.clone(), msg.chat.id, build_help_response(), config.format).await?;
             }
             Ok(Command::Info(text)) => {
-                send_large_message(bot.clone(), msg.chat.id, build_info_response(&text).await).await?;
+                let response = build_info_response(&text, &config.fallback_chain, chat_settings.urban_filter(msg.chat.is_private()), &options).await;
+                send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
+            }
+            Ok(Command::Syn(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_related_response(&text, Relation::Synonyms, &options).await, config.format).await?;
+            }
+            Ok(Command::Ant(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_related_response(&text, Relation::Antonyms, &options).await, config.format).await?;
+            }
+            Ok(Command::Rhyme(text)) => {
+                let response = build_word_groups_response(&text, "rhymes", |word| discovery::rhymes(word, Pronunciations::global()), &options);
+                send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
+            }
+            Ok(Command::SoundsLike(text)) => {
+                let response = build_word_groups_response(&text, "sound-alikes", |word| discovery::sounds_like(word, Pronunciations::global()), &options);
+                send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
+            }
+            Ok(Command::Related(text)) => {
+                let related = |word: &str| Some(discovery::related(word, Thesaurus::global(), WordNet::global(), Pronunciations::global()));
+                send_large_message(bot.clone(), msg.chat.id, build_word_groups_response(&text, "related words", related, &options), config.format).await?;
+            }
+            Ok(Command::WhatWord(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_reverse_response(&text, &options), config.format).await?;
+            }
+            Ok(Command::Pattern(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_pattern_response(&text, Query::parse, 0, &options), config.format).await?;
+            }
+            Ok(Command::Crossword(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_pattern_response(&text, Query::crossword, 0, &options), config.format).await?;
+            }
+            Ok(Command::Anagram(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_anagram_response(&text, &options), config.format).await?;
+            }
+            Ok(Command::Valid(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_validity_response(&text, &options), config.format).await?;
+            }
+            Ok(Command::Level(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_level_response(&text, &options), config.format).await?;
+            }
+            Ok(Command::Origin(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_origin_response(&text, &config.fallback_chain, &options).await, config.format).await?;
+            }
+            Ok(Command::Translate(text)) => {
+                send_large_message(bot.clone(), msg.chat.id, build_translation_response(&text, &options).await, config.format).await?;
             }
             Ok(Command::Urban(text)) => {
-                send_large_message(bot.clone(), msg.chat.id, build_urban_response(&text).await).await?;
+                send_large_message(bot.clone(), msg.chat.id, build_urban_response(&text, chat_settings.urban_filter(msg.chat.is_private()), &options).await, config.format).await?;
+            }
+            Ok(Command::UrbanRandom) => {
+                let response = build_urban_feed_response(urban::UrbanFeed::Random, msg.chat.id, &settings, chat_settings.urban_filter(msg.chat.is_private()), &options).await;
+                send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
+            }
+            Ok(Command::UrbanOfTheDay) => {
+                let response = build_urban_feed_response(urban::UrbanFeed::WordsOfTheDay, msg.chat.id, &settings, chat_settings.urban_filter(msg.chat.is_private()), &options).await;
+                send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
+            }
+            Ok(Command::Collapse(value)) => {
+                let reply = match value.trim().parse::<usize>() {
+                    Ok(threshold) => {
+                        settings.update(msg.chat.id, |chat| chat.collapse_threshold = threshold);
+                        if threshold == 0 {
+                            "Long definitions will no longer be collapsed".to_string()
+                        } else {
+                            format!("Senses after the first will be collapsed when there are more than {}", threshold)
+                        }
+                    }
+                    Err(_) => "Usage: /collapse <number of senses>".to_string(),
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+            }
+            Ok(Command::Metrics(value)) => {
+                let reply = match value.trim().to_lowercase().as_str() {
+                    "on" => {
+                        settings.update(msg.chat.id, |chat| chat.show_metrics = true);
+                        "Definitions will show syllables, frequency and level"
+                    }
+                    "off" => {
+                        settings.update(msg.chat.id, |chat| chat.show_metrics = false);
+                        "Definitions will no longer show syllables, frequency and level"
+                    }
+                    _ => "Usage: /metrics on|off",
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+            }
+            Ok(Command::UrbanSort(value)) => {
+                let reply = match value.parse::<urban::UrbanSort>() {
+                    Ok(sort) => {
+                        settings.update(msg.chat.id, |chat| chat.urban_sort = sort);
+                        format!("Urban definitions will be sorted by {:?}", sort).to_lowercase()
+                    }
+                    Err(_) => "Usage: /urbansort upstream|votes|newest".to_string(),
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+            }
+            Ok(Command::UrbanMinScore(value)) => {
+                let value = value.trim();
+                let reply = if value.eq_ignore_ascii_case("off") {
+                    settings.update(msg.chat.id, |chat| chat.urban_min_score = None);
+                    "Urban definitions will no longer be filtered by score".to_string()
+                } else {
+                    match value.parse::<i64>() {
+                        Ok(min_score) => {
+                            settings.update(msg.chat.id, |chat| chat.urban_min_score = Some(min_score));
+                            format!("Urban definitions with fewer than {} net votes will be hidden", min_score)
+                        }
+                        Err(_) => "Usage: /urbanminscore <net votes>|off".to_string(),
+                    }
+                };
+                bot.send_message(msg.chat.id, reply).await?;
+            }
+            Ok(Command::Nsfw(value)) => {
+                let reply = if !can_change_settings(&bot, &msg).await? {
+                    "Only group administrators can change this setting".to_string()
+                } else {
+                    match value.parse::<urban::NsfwPolicy>() {
+                        Ok(policy) => {
+                            settings.update(msg.chat.id, |chat| chat.nsfw_policy = Some(policy));
+                            format!("Explicit urban content policy set to {:?}", policy).to_lowercase()
+                        }
+                        Err(_) => "Usage: /nsfw allow|mask|hide".to_string(),
+                    }
+                };
+                bot.send_message(msg.chat.id, reply).await?;
             }
             Err(_) => {
                 if text.starts_with('/') {
                     bot.send_message(msg.chat.id, "Command not found!").await?;
                 } else {
-                    send_large_message(bot.clone(), msg.chat.id, build_info_response(&text).await).await?;
+                    let response = build_info_response(text, &config.fallback_chain, chat_settings.urban_filter(msg.chat.is_private()), &options).await;
+                    send_large_message(bot.clone(), msg.chat.id, response, config.format).await?;
                 }
             }
         }
@@ -88,79 +323,399 @@ async fn message_handler(
     Ok(())
 }
 
-pub async fn send_large_message(bot: Bot, chat_id: ChatId, message: String) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let max_length = 4096;
-    let mut message = message;
+/// Prefix of the callback data of buttons that look a word up.
+const LOOKUP_CALLBACK_PREFIX: &str = "info:";
 
-    while message.len() > max_length {
-        if let Some(last_newline) = message[..max_length].rfind('\n') {
-            let (chunk, rest) = message.split_at(last_newline);
-            bot.send_message(chat_id, chunk.to_string()).parse_mode(ParseMode::MarkdownV2).await?;
-            message = rest.to_string();
-        } else {
-            let (chunk, rest) = message.split_at(max_length);
-            bot.send_message(chat_id, chunk.to_string()).parse_mode(ParseMode::MarkdownV2).await?;
-            message = rest.to_string();
-        }
+/// Prefix of the callback data of buttons that turn pages of pattern matches, followed by the page
+/// and the query.
+const PATTERN_CALLBACK_PREFIX: &str = "pattern:";
+
+/// Longest callback data Telegram accepts, in bytes.
+const MAX_CALLBACK_DATA: usize = 64;
+
+async fn callback_handler(
+    bot: Bot,
+    q: CallbackQuery,
+    me: Me,
+    config: Config,
+    settings: Settings,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let (Some(data), Some(message)) = (q.data.as_deref(), q.message.as_ref()) else {
+        return Ok(());
+    };
+
+    if let Some(word) = data.strip_prefix(LOOKUP_CALLBACK_PREFIX) {
+        info!("Received lookup button: {}", word);
+        let chat = message.chat();
+        let chat_settings = settings.get(chat.id);
+        let options = chat_settings.render_options(me.username(), chat.is_private());
+        let response = build_info_response(word, &config.fallback_chain, chat_settings.urban_filter(chat.is_private()), &options).await;
+        send_large_message(bot, chat.id, response, config.format).await?;
+    } else if let Some((page, query)) = data.strip_prefix(PATTERN_CALLBACK_PREFIX).and_then(|rest| rest.split_once(':')) {
+        info!("Received pattern page {}: {}", page, query);
+        let chat = message.chat();
+        let options = settings.get(chat.id).render_options(me.username(), chat.is_private());
+        let reply = build_pattern_response(query, Query::parse, page.parse().unwrap_or(0), &options);
+        let request = bot.edit_message_text(chat.id, message.id(), reply.document.render(config.format)).parse_mode(config.format.parse_mode());
+        match reply.keyboard {
+            Some(keyboard) => request.reply_markup(keyboard).await?,
+            None => request.await?,
+        };
+    }
+
+    Ok(())
+}
+
+/// Anyone can change the settings of a private chat, only administrators those of a group.
+async fn can_change_settings(bot: &Bot, msg: &Message) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    if msg.chat.is_private() {
+        return Ok(true);
+    }
+    match &msg.from {
+        Some(user) => Ok(bot.get_chat_member(msg.chat.id, user.id).await?.is_privileged()),
+        None => Ok(false),
     }
+}
 
-    if !message.is_empty() {
-        bot.send_message(chat_id, message).parse_mode(ParseMode::MarkdownV2).await?;
+pub async fn send_large_message(bot: Bot, chat_id: ChatId, reply: impl Into<Reply>, format: Format) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let reply = reply.into();
+    let chunks = split_message(&reply.document.render(format), format, MAX_MESSAGE_LENGTH);
+    let last = chunks.len().saturating_sub(1);
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let request = bot.send_message(chat_id, chunk).parse_mode(format.parse_mode());
+        match &reply.keyboard {
+            Some(keyboard) if index == last => request.reply_markup(keyboard.clone()).await?,
+            _ => request.await?,
+        };
     }
 
     Ok(())
 }
 
-async fn build_info_response(word: &str) -> String {
-    return match dictionary::definition(word).await {
-        Ok(defs) => {
-            if defs.is_empty() {
-                "No definition found".to_string()
-            } else {
-                defs.to_message()
+fn build_help_response() -> Document {
+    let mut help = Document::new();
+    help.text(&Command::descriptions().to_string());
+    help
+}
+
+/// Answers from the first source of `chain` that knows the word, saying so when it is not the first one.
+/// A sentence is only asked to the sources of the sentence chain, first as a whole and otherwise
+/// with a glossary of its words.
+async fn build_info_response(word: &str, chain: &[Source], filter: UrbanFilter, options: &RenderOptions) -> Reply {
+    if sentence::is_sentence(word) {
+        let failure = match lookup::lookup(word, &sentence::chain(chain), filter).await {
+            Ok(found) => return build_found_response(found, chain, options).into(),
+            Err(e) => Some(e).filter(|e| e.kind != DictionaryErrorKind::NotFound),
+        };
+        let glossary = sentence::define(word, chain, filter).await;
+        if !glossary.is_empty() {
+            return glossary.build_message(options).into();
+        }
+        return match failure {
+            Some(e) => build_error_response(&e).into(),
+            None => build_suggestion_response(word),
+        };
+    }
+
+    return match lookup::lookup(word, chain, filter).await {
+        Ok(found) => build_found_response(found, chain, options).into(),
+        Err(e) if e.kind == DictionaryErrorKind::NotFound => build_suggestion_response(word),
+        Err(e) => build_error_response(&e).into(),
+    };
+}
+
+fn build_found_response(found: Found, chain: &[Source], options: &RenderOptions) -> Document {
+    let mut message = Document::new();
+    if let Some(lemma) = found.lemma {
+        message.italic(&format!("{} → {} ({})", found.term, lemma.lemma, lemma.form)).newline();
+    }
+    if let Some(kind) = found.phrase_kind {
+        message.italic(&format!("{} ({})", found.term, kind.label())).newline();
+    }
+    if chain.first() != Some(&found.source) {
+        message.italic(&format!("From {}", found.source.label())).newline();
+    }
+    match found.answer {
+        Answer::Definitions(defs) => message.append(&defs.build_message(options)),
+        Answer::Urban(defs) => message.append(&defs.build_message(options)),
+    };
+    message
+}
+
+/// Offers listed words close to a missing one as buttons that look them up.
+fn build_suggestion_response(word: &str) -> Reply {
+    let mut message = Document::new();
+    message.text("No definition found");
+    let suggestions = Suggester::global().suggest(word, MAX_SUGGESTIONS);
+    if suggestions.is_empty() {
+        return message.into();
+    }
+
+    message.text(". Did you mean:");
+    let buttons = suggestions.iter()
+        .map(|suggestion| InlineKeyboardButton::callback(*suggestion, format!("{}{}", LOOKUP_CALLBACK_PREFIX, suggestion)));
+    Reply {
+        document: message,
+        keyboard: Some(InlineKeyboardMarkup::new([buttons])),
+    }
+}
+
+/// Lists the related words of `word` found in the dictionary and the local thesaurus. The thesaurus
+/// still answers when the dictionary cannot be reached.
+async fn build_related_response(word: &str, relation: Relation, options: &RenderOptions) -> Document {
+    let term = match normalize::normalize_term(word) {
+        Ok(term) => term,
+        Err(e) => return build_error_response(&e),
+    };
+    let result = dictionary::definition(&term).await;
+    let related = thesaurus::collect(&term, result.as_deref().unwrap_or_default(), relation, Thesaurus::global());
+    if !related.is_empty() {
+        return related.build_message(options);
+    }
+
+    match result {
+        Err(e) if e.kind != DictionaryErrorKind::NotFound => build_error_response(&e),
+        _ => {
+            let mut message = Document::new();
+            message.text(&format!("No {} found for ", relation.label())).italic(&term);
+            message
+        }
+    }
+}
+
+/// Groups of words found by `find` for `word`, named `kind` when there are none. `find` returns
+/// `None` when it cannot tell how the word sounds.
+fn build_word_groups_response(word: &str, kind: &str, find: impl Fn(&str) -> Option<WordGroups>, options: &RenderOptions) -> Document {
+    let term = match normalize::normalize_term(word) {
+        Ok(term) => term,
+        Err(e) => return build_error_response(&e),
+    };
+    let mut message = Document::new();
+    match find(&term) {
+        Some(groups) if !groups.is_empty() => return groups.build_message(options),
+        Some(_) => message.text(&format!("No {} found for ", kind)).italic(&term),
+        None => message.text("No pronunciation known for ").italic(&term),
+    };
+    message
+}
+
+/// Words of the offline dictionaries whose definitions match `description`.
+fn build_reverse_response(description: &str, options: &RenderOptions) -> Document {
+    let description = description.trim();
+    let mut message = Document::new();
+    if description.is_empty() {
+        message.text("Describe the word you are looking for, as in /whatword unable to sleep");
+        return message;
+    }
+
+    let candidates = ReverseIndex::global().search(description, MAX_CANDIDATES);
+    if candidates.is_empty() {
+        message.text("No words found for ").italic(description);
+        return message;
+    }
+    message.bold("Words for").text(" ").italic(description).text(":").newline();
+    message.append(&candidates.build_message(options));
+    message
+}
+
+/// Page `page` of the listed words matching the query `parse` reads from `text`, with buttons to the pages around it.
+fn build_pattern_response(text: &str, parse: fn(&str) -> Result<Query, DictionaryError>, page: usize, options: &RenderOptions) -> Reply {
+    if text.trim().is_empty() {
+        let mut message = Document::new();
+        message.text("Give a pattern, as in /pattern c?t* or /crossword _a_e 4");
+        return message.into();
+    }
+    let query = match parse(text) {
+        Ok(query) => query,
+        Err(e) => return build_error_response(&e).into(),
+    };
+
+    let found = PatternIndex::global().search(&query, ReverseIndex::global());
+    if found.is_empty() {
+        let mut message = Document::new();
+        message.text("No words found for ").italic(&query.to_string());
+        return message.into();
+    }
+
+    let matches = Matches::new(&query, &found, page);
+    let mut buttons = Vec::new();
+    if matches.page > 0 {
+        buttons.push(("« Previous", matches.page - 1));
+    }
+    if matches.page + 1 < matches.pages {
+        buttons.push(("Next »", matches.page + 1));
+    }
+    let buttons: Vec<InlineKeyboardButton> = buttons.into_iter()
+        .map(|(label, page)| (label, format!("{}{}:{}", PATTERN_CALLBACK_PREFIX, page, matches.query)))
+        .filter(|(_, data)| data.len() <= MAX_CALLBACK_DATA)
+        .map(|(label, data)| InlineKeyboardButton::callback(label, data))
+        .collect();
+    Reply {
+        document: matches.build_message(options),
+        keyboard: (!buttons.is_empty()).then(|| InlineKeyboardMarkup::new([buttons])),
+    }
+}
+
+/// Listed words made of the letters of `text`, longest first.
+fn build_anagram_response(text: &str, options: &RenderOptions) -> Document {
+    let mut message = Document::new();
+    if !text.chars().any(char::is_alphabetic) {
+        message.text("Give letters to rearrange, as in /anagram listen");
+        return message;
+    }
+    match anagram::anagrams(text, AnagramIndex::global()) {
+        Ok(anagrams) if !anagrams.is_empty() => anagrams.build_message(options),
+        Ok(anagrams) => {
+            message.text("No anagrams found for ").italic(&anagrams.letters);
+            message
+        }
+        Err(e) => build_error_response(&e),
+    }
+}
+
+/// Whether the first word of `text` is allowed by the word lists named after it, with a button
+/// looking it up.
+fn build_validity_response(text: &str, options: &RenderOptions) -> Reply {
+    let mut fields = text.split_whitespace();
+    let Some(word) = fields.next() else {
+        let mut message = Document::new();
+        message.text(&format!("Give a word to check, as in /valid qi collins. Word lists: {}", Lexicons::global().names().join(", ")));
+        return message.into();
+    };
+    let names: Vec<&str> = fields.collect();
+    match Lexicons::global().check(word, &names) {
+        Ok(validity) => {
+            let data = format!("{}{}", LOOKUP_CALLBACK_PREFIX, validity.word);
+            let button = (data.len() <= MAX_CALLBACK_DATA).then(|| InlineKeyboardButton::callback(format!("Define {}", validity.word), data));
+            Reply {
+                document: validity.build_message(options),
+                keyboard: button.map(|button| InlineKeyboardMarkup::new([[button]])),
             }
         }
-        Err(e) => {
-            let default_message = match e.kind {
-                DictionaryErrorKind::NotFound => "No definition found",
-                DictionaryErrorKind::Parse => "Error on parse output",
-                DictionaryErrorKind::Unknown => "An unknown error occurred"
-            };
-
-            let message = if !e.message.is_empty() {
-                format!("{}\n`{}`", default_message, e.message)
-            } else {
-                default_message.to_string()
-            };
+        Err(e) => build_error_response(&e).into(),
+    }
+}
 
+/// Syllables, frequency band and estimated level of `word`.
+fn build_level_response(word: &str, options: &RenderOptions) -> Document {
+    match normalize::normalize_term(word) {
+        Ok(term) => FrequencyList::global().metrics(&term, WordList::global(), Pronunciations::global()).build_message(options),
+        Err(e) => build_error_response(&e),
+    }
+}
+
+/// Etymology of `word` from the first source of `chain` that has one.
+async fn build_origin_response(word: &str, chain: &[Source], options: &RenderOptions) -> Document {
+    match etymology::etymology(word, chain).await {
+        Ok(etymology) => etymology.build_message(options),
+        Err(e) if e.kind == DictionaryErrorKind::NotFound => {
+            let mut message = Document::new();
+            message.text("No etymology available for ").italic(word.trim());
             message
         }
+        Err(e) => build_error_response(&e),
+    }
+}
+
+/// Lists the translations of a word from the Wiktionary index. A last word naming a language the
+/// rest has translations into keeps only those.
+async fn build_translation_response(text: &str, options: &RenderOptions) -> Document {
+    let term = match normalize::normalize_term(text) {
+        Ok(term) => term,
+        Err(e) => return build_error_response(&e),
+    };
+    // Entries are read from disk, so the lookup runs off the async threads.
+    let lookup = tokio::task::spawn_blocking(move || {
+        let wiktionary = Wiktionary::global();
+        let (word, language) = match term.rsplit_once(' ') {
+            Some((word, language)) if wiktionary.translations(word, Some(language)).is_ok_and(|found| !found.is_empty()) => {
+                (word.to_string(), Some(language.to_string()))
+            }
+            _ => (term.clone(), None),
+        };
+        let translations = wiktionary.translations(&word, language.as_deref());
+        (word, language, translations)
+    });
+    let (word, language, translations) = match lookup.await {
+        Ok(found) => found,
+        Err(err) => {
+            return build_error_response(&DictionaryError {
+                kind: DictionaryErrorKind::Unknown,
+                message: err.to_string(),
+            });
+        }
     };
+
+    let mut message = Document::new();
+    match translations {
+        Ok(translations) if !translations.is_empty() => {
+            message.bold("Translations of").text(" ").italic(&word);
+            if language.is_some() {
+                message.text(&format!(" into {}", translations[0].lang));
+            }
+            message.text(":").newline();
+            message.append(&translations.build_message(options));
+        }
+        Ok(_) => {
+            message.text("No translations found for ").italic(&word);
+        }
+        Err(e) => return build_error_response(&e),
+    }
+    message
 }
 
-async fn build_urban_response(word: &str) -> String {
-    return match urban::definition(word).await {
+async fn build_urban_response(word: &str, filter: UrbanFilter, options: &RenderOptions) -> Document {
+    let term = match normalize::normalize_term(word) {
+        Ok(term) => term,
+        Err(e) => return build_error_response(&e),
+    };
+    return match urban::definition(&term).await {
         Ok(defs) => {
+            let defs = filter.apply(defs);
             if defs.is_empty() {
-                "No definition found".to_string()
+                let mut message = Document::new();
+                message.text("No definition found");
+                message
             } else {
-                defs.to_message()
+                defs.build_message(options)
             }
         }
-        Err(e) => {
-            let default_message = match e.kind {
-                DictionaryErrorKind::NotFound => "No definition found",
-                DictionaryErrorKind::Parse => "Error on parse output",
-                DictionaryErrorKind::Unknown => "An unknown error occurred"
-            };
-
-            let message = if !e.message.is_empty() {
-                format!("{}\n`{}`", default_message, e.message)
-            } else {
-                default_message.to_string()
-            };
+        Err(e) => build_error_response(&e),
+    };
+}
 
-            message
+/// Shows the first entry of `feed` the chat has not seen yet and remembers it.
+async fn build_urban_feed_response(feed: urban::UrbanFeed, chat_id: ChatId, settings: &Settings, filter: UrbanFilter, options: &RenderOptions) -> Document {
+    for _ in 0..feed.attempts() {
+        let defs = match feed.fetch().await {
+            Ok(defs) => filter.apply(defs),
+            Err(e) => return build_error_response(&e),
+        };
+        if let Some(def) = urban::first_unseen(defs, &settings.get(chat_id).recent_urban) {
+            settings.update(chat_id, |chat| chat.remember_urban(def.id()));
+            return def.build_message(options);
         }
+    }
+
+    let mut message = Document::new();
+    message.text("Nothing new right now, try again later");
+    message
+}
+
+fn build_error_response(e: &DictionaryError) -> Document {
+    let default_message = match e.kind {
+        DictionaryErrorKind::NotFound => "No definition found",
+        DictionaryErrorKind::Parse => "Error on parse output",
+        DictionaryErrorKind::Unknown => "An unknown error occurred",
+        DictionaryErrorKind::InvalidInput => "This cannot be looked up",
     };
-}
\ No newline at end of file
+
+    let mut message = Document::new();
+    message.text(default_message);
+    if !e.message.is_empty() {
+        message.newline().code(&e.message);
+    }
+
+    message
+}
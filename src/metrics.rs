@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+use crate::lemma::Lemmatizer;
+use crate::pronunciation::Pronunciations;
+use crate::words::WordList;
+
+const BUNDLED_FREQUENCY: &str = include_str!("metrics/frequency.txt");
+
+/// How often a word is used, from its rank in the frequency list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Band {
+    /// Among the 1,000 most frequent words.
+    VeryCommon,
+    /// Among the 5,000 most frequent words, or in the shared word list of common words.
+    Common,
+    /// Among the 20,000 most frequent words.
+    Uncommon,
+    Rare,
+}
+
+impl Band {
+    fn from_rank(rank: usize) -> Self {
+        match rank {
+            0..=1000 => Band::VeryCommon,
+            1001..=5000 => Band::Common,
+            5001..=20000 => Band::Uncommon,
+            _ => Band::Rare,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Band::VeryCommon => "very common",
+            Band::Common => "common",
+            Band::Uncommon => "uncommon",
+            Band::Rare => "rare",
+        }
+    }
+}
+
+/// Level of the Common European Framework of Reference at which learners usually know a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    A1,
+    A2,
+    B1,
+    B2,
+    C1,
+    C2,
+}
+
+impl Level {
+    /// Level estimated from how frequent a word is, as learners meet frequent words first.
+    fn estimate(rank: Option<usize>, band: Band) -> Self {
+        match (rank, band) {
+            (Some(..=500), _) => Level::A1,
+            (Some(..=1000), _) => Level::A2,
+            (Some(..=2000), _) | (None, Band::Common) => Level::B1,
+            (Some(..=4000), _) => Level::B2,
+            (Some(..=8000), _) => Level::C1,
+            _ => Level::C2,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Syllables, frequency and estimated level of a word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordMetrics {
+    pub word: String,
+    pub syllables: usize,
+    /// Rank in the frequency list, 1 being the most frequent word.
+    pub rank: Option<usize>,
+    pub band: Band,
+    pub level: Level,
+}
+
+/// Ranks of words in a frequency list.
+#[derive(Debug, Default)]
+pub struct FrequencyList {
+    ranks: HashMap<String, usize>,
+}
+
+impl FrequencyList {
+    /// Reads words from the most frequent on, one per line and optionally followed by a count,
+    /// skipping `#` comments and repeated words.
+    pub fn parse(source: &str) -> Self {
+        let mut list = FrequencyList::default();
+        let words = source.lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_whitespace().next());
+        for word in words {
+            let rank = list.ranks.len() + 1;
+            list.ranks.entry(word.to_lowercase()).or_insert(rank);
+        }
+        list
+    }
+
+    /// Bundled list of the most frequent words, replaced by the list in the file named by
+    /// `WORDBOT_FREQUENCY`, if any.
+    pub fn global() -> &'static FrequencyList {
+        static FREQUENCY: OnceLock<FrequencyList> = OnceLock::new();
+        FREQUENCY.get_or_init(|| {
+            if let Ok(path) = env::var("WORDBOT_FREQUENCY") {
+                match fs::read_to_string(&path) {
+                    Ok(source) => {
+                        info!("Loaded frequency list from {}", path);
+                        return FrequencyList::parse(&source);
+                    }
+                    Err(err) => warn!("Failed to read frequency list {}: {}", path, err),
+                }
+            }
+            FrequencyList::parse(BUNDLED_FREQUENCY)
+        })
+    }
+
+    /// Rank of `word`, or of its lemma when the inflected form is not listed.
+    fn rank(&self, word: &str) -> Option<usize> {
+        self.ranks.get(word).copied()
+            .or_else(|| Lemmatizer::global().lemmatize(word).and_then(|lemma| self.ranks.get(&lemma.lemma).copied()))
+    }
+
+    /// Metrics of `word`. Words missing from the list are common when `words` has them, and rare
+    /// otherwise.
+    pub fn metrics(&self, word: &str, words: &WordList, pronunciations: &Pronunciations) -> WordMetrics {
+        let word = word.trim().to_lowercase();
+        let rank = self.rank(&word);
+        let band = match rank {
+            Some(rank) => Band::from_rank(rank),
+            None if words.contains(&word) => Band::Common,
+            None => Band::Rare,
+        };
+        WordMetrics {
+            syllables: pronunciations.syllables(&word),
+            level: Level::estimate(rank, band),
+            rank,
+            band,
+            word,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(word: &str) -> WordMetrics {
+        let list = FrequencyList::parse("# header\nthe 100\nof 90\nThe 80\nhouse\n");
+        list.metrics(word, &WordList::new(["abandon"]), &Pronunciations::default())
+    }
+
+    #[test]
+    fn test_parse_ranks_in_order() {
+        assert_eq!(metrics("of").rank, Some(2));
+        assert_eq!(metrics(" HOUSE ").rank, Some(3));
+        assert_eq!(metrics("house").syllables, 1);
+    }
+
+    #[test]
+    fn test_bands_and_levels() {
+        let house = metrics("house");
+        assert_eq!((house.band, house.level), (Band::VeryCommon, Level::A1));
+        let abandon = metrics("abandon");
+        assert_eq!((abandon.rank, abandon.band, abandon.level), (None, Band::Common, Level::B1));
+        let zeugma = metrics("zeugma");
+        assert_eq!((zeugma.band, zeugma.level), (Band::Rare, Level::C2));
+
+        assert_eq!(Band::from_rank(4000), Band::Common);
+        assert_eq!(Level::estimate(Some(3000), Band::Common), Level::B2);
+        assert_eq!(Level::estimate(Some(15000), Band::Uncommon), Level::C2);
+    }
+
+    #[test]
+    fn test_bundled_list() {
+        let list = FrequencyList::parse(BUNDLED_FREQUENCY);
+        assert_eq!(list.rank("the"), Some(1));
+        assert_eq!(list.rank("children"), list.rank("child"));
+        assert!(list.rank("children").is_some());
+    }
+}
@@ -0,0 +1,69 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::dictionary::{DictionaryError, DictionaryErrorKind};
+
+/// Longest term, in characters, worth sending to a dictionary.
+pub const MAX_TERM_LENGTH: usize = 64;
+
+/// Characters removed from both ends of a term, such as the quotes and full stop around "'word.'".
+const EDGE_PUNCTUATION: &[char] = &[
+    '.', ',', ';', ':', '!', '?', '"', '\'', '`', '(', ')', '[', ']', '{', '}', '<', '>', '«', '»', '*', '_', '~',
+];
+
+/// Cleans what a user typed into a term dictionaries can look up: NFC normalized, with typographic
+/// quotes and dashes made plain, whitespace collapsed, surrounding punctuation removed and in
+/// lowercase. Text that cannot be a word or phrase is rejected with [`DictionaryErrorKind::InvalidInput`].
+pub fn normalize_term(input: &str) -> Result<String, DictionaryError> {
+    let plain: String = input.nfc()
+        .map(|c| match c {
+            '‘' | '’' | '‛' | '′' => '\'',
+            '“' | '”' | '„' | '″' => '"',
+            '‐' | '‑' | '‒' | '–' | '—' => '-',
+            c => c,
+        })
+        .collect();
+    let term = plain.split_whitespace().collect::<Vec<_>>().join(" ");
+    let term = term.trim_matches(|c: char| EDGE_PUNCTUATION.contains(&c) || c.is_whitespace()).to_lowercase();
+
+    if term.is_empty() || !term.chars().any(char::is_alphabetic) {
+        return Err(invalid_input("Send a word or a short phrase"));
+    }
+    if term.chars().count() > MAX_TERM_LENGTH {
+        return Err(invalid_input(&format!("Terms are limited to {} characters", MAX_TERM_LENGTH)));
+    }
+    if term.contains("://") || term.starts_with("www.") || term.chars().any(char::is_control) {
+        return Err(invalid_input("Links and formatted text cannot be looked up"));
+    }
+    Ok(term)
+}
+
+fn invalid_input(message: &str) -> DictionaryError {
+    DictionaryError {
+        kind: DictionaryErrorKind::InvalidInput,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cleans_term() {
+        assert_eq!(normalize_term("  “Hello,   World!”  ").unwrap(), "hello, world");
+        assert_eq!(normalize_term("rock’n’roll").unwrap(), "rock'n'roll");
+        assert_eq!(normalize_term("self–esteem").unwrap(), "self-esteem");
+    }
+
+    #[test]
+    fn test_composes_accents() {
+        assert_eq!(normalize_term("cafe\u{301}").unwrap(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn test_rejects_non_words() {
+        for input in ["", " ?! ", "12345", "https://example.com/a?b", &"a".repeat(MAX_TERM_LENGTH + 1)] {
+            assert_eq!(normalize_term(input).unwrap_err().kind, DictionaryErrorKind::InvalidInput, "{:?}", input);
+        }
+    }
+}
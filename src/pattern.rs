@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::dictionary::{DictionaryError, DictionaryErrorKind};
+use crate::reverse::ReverseIndex;
+use crate::words::WordList;
+
+/// Words shown on one page of matches.
+pub const PAGE_SIZE: usize = 30;
+
+/// Longest pattern accepted, in letters and wildcards.
+const MAX_PATTERN_LENGTH: usize = 32;
+
+/// Piece of a pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Letter(char),
+    /// Any one letter, written `?` or `_`.
+    One,
+    /// Any number of letters, written `*`.
+    Any,
+}
+
+/// Search for words by spelling: a pattern, an optional length such as `5` or `4-6`, and optional
+/// words their definitions should match, as in "c?t* 3-5 animal".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    tokens: Vec<Token>,
+    length: Option<(usize, usize)>,
+    pub description: String,
+}
+
+fn invalid(message: String) -> DictionaryError {
+    DictionaryError { kind: DictionaryErrorKind::InvalidInput, message }
+}
+
+/// Reads a length such as `5` or `4-6`.
+fn parse_length(text: &str) -> Option<(usize, usize)> {
+    let (min, max) = text.split_once('-').unwrap_or((text, text));
+    let (min, max) = (min.parse().ok()?, max.parse().ok()?);
+    (min <= max).then_some((min, max))
+}
+
+impl Query {
+    /// Reads `/pattern` arguments, where `?` or `_` stands for one letter and `*` for any number
+    /// of them.
+    pub fn parse(text: &str) -> Result<Query, DictionaryError> {
+        let text = text.trim().to_lowercase();
+        let mut fields = text.split_whitespace().peekable();
+        let pattern = fields.next().ok_or_else(|| invalid("Empty pattern".to_string()))?;
+
+        let mut tokens = Vec::new();
+        for c in pattern.chars() {
+            let token = match c {
+                '?' | '_' => Token::One,
+                '*' => Token::Any,
+                c if c.is_alphabetic() => Token::Letter(c),
+                c => return Err(invalid(format!("Unexpected {} in pattern", c))),
+            };
+            if !(token == Token::Any && tokens.last() == Some(&Token::Any)) {
+                tokens.push(token);
+            }
+        }
+        if tokens.len() > MAX_PATTERN_LENGTH {
+            return Err(invalid(format!("Patterns are limited to {} letters", MAX_PATTERN_LENGTH)));
+        }
+
+        let length = fields.peek().and_then(|field| parse_length(field));
+        if length.is_some() {
+            fields.next();
+        }
+        let description = fields.collect::<Vec<_>>().join(" ");
+        Ok(Query { tokens, length, description })
+    }
+
+    /// Reads `/crossword` arguments, as in "_a_e 4", where letters past the pattern up to the
+    /// length are unknown.
+    pub fn crossword(text: &str) -> Result<Query, DictionaryError> {
+        let mut query = Query::parse(text)?;
+        if let Some((_, max)) = query.length {
+            if !query.tokens.contains(&Token::Any) && max > query.tokens.len() {
+                query.tokens.push(Token::Any);
+            }
+        }
+        Ok(query)
+    }
+
+    /// Letters and single wildcards, which every matching word has at least as many letters as.
+    fn fixed_length(&self) -> usize {
+        self.tokens.iter().filter(|token| **token != Token::Any).count()
+    }
+
+    /// Letters known at a position of every word of `length` letters: those before the first `*`
+    /// counted from the start, and those after the last `*` counted from the end.
+    fn known_letters(&self, length: usize) -> Vec<(usize, char)> {
+        let first = self.tokens.iter().position(|token| *token == Token::Any).unwrap_or(self.tokens.len());
+        let last = self.tokens.iter().rposition(|token| *token == Token::Any).map_or(self.tokens.len(), |index| index + 1);
+        let prefix = self.tokens[..first].iter().enumerate();
+        let suffix = self.tokens[last..].iter().enumerate().map(|(index, token)| (length - (self.tokens.len() - last) + index, token));
+        prefix.chain(suffix)
+            .filter_map(|(position, token)| match token {
+                Token::Letter(c) => Some((position, *c)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether `word` is spelled as the pattern says.
+    fn matches(&self, word: &[char]) -> bool {
+        let (mut token, mut letter) = (0, 0);
+        // Where the last `*` started and how many letters it has taken so far.
+        let mut star: Option<(usize, usize)> = None;
+        while letter < word.len() {
+            match self.tokens.get(token) {
+                Some(Token::Any) => {
+                    star = Some((token, letter));
+                    token += 1;
+                }
+                Some(Token::One) => {
+                    token += 1;
+                    letter += 1;
+                }
+                Some(Token::Letter(c)) if *c == word[letter] => {
+                    token += 1;
+                    letter += 1;
+                }
+                _ => match star {
+                    Some((star_token, star_letter)) => {
+                        star = Some((star_token, star_letter + 1));
+                        token = star_token + 1;
+                        letter = star_letter + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.tokens[token..].iter().all(|token| *token == Token::Any)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            match token {
+                Token::Letter(c) => write!(f, "{}", c)?,
+                Token::One => write!(f, "?")?,
+                Token::Any => write!(f, "*")?,
+            }
+        }
+        match self.length {
+            Some((min, max)) if min == max => write!(f, " {}", min)?,
+            Some((min, max)) => write!(f, " {}-{}", min, max)?,
+            None => {}
+        }
+        if !self.description.is_empty() {
+            write!(f, " {}", self.description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Index of a word list by length and by the letter at each position, so that a pattern only
+/// checks the words of the right length sharing its rarest known letter.
+#[derive(Debug, Default)]
+pub struct PatternIndex {
+    words: Vec<String>,
+    /// Words of each length in letters.
+    lengths: BTreeMap<usize, Vec<usize>>,
+    /// Words of a length holding a letter at a position, keyed by length, position and letter.
+    letters: HashMap<(usize, usize, char), Vec<usize>>,
+}
+
+impl PatternIndex {
+    /// Indexes the words made of letters only, sorted by length and then alphabetically.
+    pub fn new(words: &[String]) -> Self {
+        let mut sorted: Vec<&String> = words.iter().filter(|word| word.chars().all(char::is_alphabetic)).collect();
+        sorted.sort_by_key(|word| (word.chars().count(), word.as_str()));
+
+        let mut index = PatternIndex::default();
+        for (id, word) in sorted.into_iter().enumerate() {
+            let length = word.chars().count();
+            index.lengths.entry(length).or_default().push(id);
+            for (position, c) in word.chars().enumerate() {
+                index.letters.entry((length, position, c)).or_default().push(id);
+            }
+            index.words.push(word.clone());
+        }
+        index
+    }
+
+    /// Index over the shared [`WordList`].
+    pub fn global() -> &'static PatternIndex {
+        static INDEX: OnceLock<PatternIndex> = OnceLock::new();
+        INDEX.get_or_init(|| PatternIndex::new(WordList::global().words()))
+    }
+
+    /// Listed words spelled as `query` says, shortest first and then alphabetically.
+    fn matches(&self, query: &Query) -> Vec<&str> {
+        let fixed = query.fixed_length();
+        let (min, max) = query.length.unwrap_or((0, usize::MAX));
+        let max = if query.tokens.contains(&Token::Any) { max } else { max.min(fixed) };
+        if min.max(fixed) > max {
+            return Vec::new();
+        }
+
+        let mut found = Vec::new();
+        for (&length, ids) in self.lengths.range(min.max(fixed)..=max) {
+            let mut candidates: &[usize] = ids;
+            for (position, c) in query.known_letters(length) {
+                match self.letters.get(&(length, position, c)) {
+                    Some(having) if having.len() < candidates.len() => candidates = having,
+                    Some(_) => {}
+                    None => {
+                        candidates = &[];
+                        break;
+                    }
+                }
+            }
+            for &id in candidates {
+                let chars: Vec<char> = self.words[id].chars().collect();
+                if query.matches(&chars) {
+                    found.push(self.words[id].as_str());
+                }
+            }
+        }
+        found
+    }
+
+    /// Words matching `query`. With a description, only words whose definitions match it are kept,
+    /// best matches first.
+    pub fn search(&self, query: &Query, reverse: &ReverseIndex) -> Vec<&str> {
+        let found = self.matches(query);
+        if query.description.is_empty() {
+            return found;
+        }
+        let found: HashSet<&str> = found.into_iter().collect();
+        reverse.search(&query.description, usize::MAX).into_iter()
+            .filter_map(|candidate| found.get(candidate.word.to_lowercase().as_str()).copied())
+            .collect()
+    }
+}
+
+/// One page of the words matching a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matches {
+    pub query: String,
+    pub words: Vec<String>,
+    /// Page shown, counted from zero.
+    pub page: usize,
+    pub pages: usize,
+    pub total: usize,
+}
+
+impl Matches {
+    /// Page `page` of `found`, or its last page when there are fewer.
+    pub fn new(query: &Query, found: &[&str], page: usize) -> Self {
+        let pages = found.len().div_ceil(PAGE_SIZE).max(1);
+        let page = page.min(pages - 1);
+        let words = found.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE).map(|word| word.to_string()).collect();
+        Matches { query: query.to_string(), words, page, pages, total: found.len() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> PatternIndex {
+        let words: Vec<String> = ["cat", "cot", "cut", "coat", "cater", "catalog", "act", "cane", "date", "game", "ice-cream"]
+            .iter().map(|word| word.to_string()).collect();
+        PatternIndex::new(&words)
+    }
+
+    fn search(query: Query) -> Vec<String> {
+        index().search(&query, &ReverseIndex::default()).into_iter().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_pattern_length_and_description() {
+        let query = Query::parse(" C??*  3-5 small animal ").unwrap();
+        assert_eq!(query.tokens, vec![Token::Letter('c'), Token::One, Token::One, Token::Any]);
+        assert_eq!(query.length, Some((3, 5)));
+        assert_eq!(query.description, "small animal");
+        assert_eq!(Query::parse(&query.to_string()).unwrap(), query);
+
+        assert_eq!(Query::parse("c**t").unwrap().to_string(), "c*t");
+        assert_eq!(Query::parse("c!t").unwrap_err().kind, DictionaryErrorKind::InvalidInput);
+        assert!(Query::parse("  ").is_err());
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert_eq!(search(Query::parse("c?t").unwrap()), vec!["cat", "cot", "cut"]);
+        assert_eq!(search(Query::parse("c?t*").unwrap()), vec!["cat", "cot", "cut", "cater", "catalog"]);
+        assert_eq!(search(Query::parse("*a*e").unwrap()), vec!["cane", "date", "game"]);
+        assert_eq!(search(Query::parse("c*t 4").unwrap()), vec!["coat"]);
+        assert_eq!(search(Query::parse("*").unwrap()).len(), 10);
+    }
+
+    #[test]
+    fn test_crossword_pads_to_length() {
+        assert_eq!(search(Query::crossword("_a_e 4").unwrap()), vec!["cane", "date", "game"]);
+        assert_eq!(search(Query::crossword("ca 5").unwrap()), vec!["cater"]);
+        assert_eq!(Query::crossword("ca 5").unwrap().to_string(), "ca* 5");
+        assert!(search(Query::crossword("cat 2").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_description_keeps_matching_words() {
+        let mut reverse = ReverseIndex::default();
+        reverse.add(vec!["cat".to_string()], "a small domesticated feline animal");
+        reverse.add(vec!["game".to_string()], "wild animals hunted for food");
+        let query = Query::parse("*a* feline").unwrap();
+        assert_eq!(index().search(&query, &reverse), vec!["cat"]);
+    }
+
+    #[test]
+    fn test_pages() {
+        let words: Vec<String> = (0..65).map(|n| format!("w{}", n)).collect();
+        let found: Vec<&str> = words.iter().map(String::as_str).collect();
+        let query = Query::parse("w*").unwrap();
+        let matches = Matches::new(&query, &found, 2);
+        assert_eq!((matches.page, matches.pages, matches.total), (2, 3, 65));
+        assert_eq!(matches.words.len(), 5);
+        assert_eq!(Matches::new(&query, &found, 9).page, 2);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+const BUNDLED_CMUDICT: &str = include_str!("pronunciation/cmudict.txt");
+
+/// How closely two words rhyme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RhymeQuality {
+    /// Same sounds from the last stressed vowel on, as "cat" and "acrobat".
+    Perfect,
+    /// Same vowels or a same last syllable, as "light" and "like".
+    Near,
+}
+
+impl RhymeQuality {
+    pub fn label(self) -> &'static str {
+        match self {
+            RhymeQuality::Perfect => "perfect rhymes",
+            RhymeQuality::Near => "near rhymes",
+        }
+    }
+}
+
+/// How alike two words sound as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Likeness {
+    /// Homophones, as "write" and "right".
+    Same,
+    /// One sound apart, as "write" and "light".
+    Close,
+}
+
+impl Likeness {
+    pub fn label(self) -> &'static str {
+        match self {
+            Likeness::Same => "same sound",
+            Likeness::Close => "one sound apart",
+        }
+    }
+}
+
+/// Pronouncing dictionary in the CMUdict format, mapping words to ARPAbet phonemes.
+#[derive(Debug, Default)]
+pub struct Pronunciations {
+    entries: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+impl Pronunciations {
+    /// Reads lines of `WORD  PH1 PH2 ...`, where alternatives are written `WORD(2)`, skipping
+    /// `;;;` comments and `#` notes at the end of lines.
+    pub fn parse(source: &str) -> Self {
+        let mut pronunciations = Pronunciations::default();
+        for line in source.lines().filter(|line| !line.starts_with(";;;")) {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next() else {
+                continue;
+            };
+            let phonemes: Vec<String> = fields.map(str::to_uppercase).collect();
+            if phonemes.is_empty() {
+                warn!("Skipping pronunciation of {} without phonemes", word);
+                continue;
+            }
+            let word = word.split_once('(').map_or(word, |(word, _)| word).to_lowercase();
+            let alternatives = pronunciations.entries.entry(word).or_default();
+            if !alternatives.contains(&phonemes) {
+                alternatives.push(phonemes);
+            }
+        }
+        pronunciations
+    }
+
+    /// Bundled common words, extended with the dictionary named by `WORDBOT_CMUDICT`, if any.
+    pub fn global() -> &'static Pronunciations {
+        static PRONUNCIATIONS: OnceLock<Pronunciations> = OnceLock::new();
+        PRONUNCIATIONS.get_or_init(|| {
+            let mut source = BUNDLED_CMUDICT.to_string();
+            if let Ok(path) = env::var("WORDBOT_CMUDICT") {
+                match fs::read_to_string(&path) {
+                    Ok(extra) => {
+                        info!("Loaded pronunciations from {}", path);
+                        source.push('\n');
+                        source.push_str(&extra);
+                    }
+                    Err(err) => warn!("Failed to read pronunciations {}: {}", path, err),
+                }
+            }
+            Pronunciations::parse(&source)
+        })
+    }
+
+    pub fn get(&self, word: &str) -> &[Vec<String>] {
+        self.entries.get(&word.trim().to_lowercase()).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Syllables of `word`: its vowel sounds when its pronunciation is known, otherwise guessed
+    /// from its spelling.
+    pub fn syllables(&self, word: &str) -> usize {
+        match self.get(word).first() {
+            Some(phonemes) => phonemes.iter().filter(|phoneme| is_vowel(phoneme)).count().max(1),
+            None => estimate_syllables(word),
+        }
+    }
+
+    /// Words rhyming with `word` and how well, best first and then by spelling. Empty when the
+    /// pronunciation of `word` is not known.
+    pub fn rhymes(&self, word: &str) -> Vec<(&str, RhymeQuality)> {
+        let word = word.trim().to_lowercase();
+        let endings: Vec<Vec<&str>> = self.get(&word).iter().map(|phonemes| plain(rhyme_part(phonemes))).collect();
+        self.best_matches(&word, |phonemes| {
+            let ending = plain(rhyme_part(phonemes));
+            endings.iter().filter_map(|wanted| rhyme_quality(wanted, &ending)).min()
+        })
+    }
+
+    /// Words sounding like `word` as a whole, closest first and then by spelling.
+    pub fn sounds_like(&self, word: &str) -> Vec<(&str, Likeness)> {
+        let word = word.trim().to_lowercase();
+        let sounds: Vec<Vec<&str>> = self.get(&word).iter().map(|phonemes| plain(phonemes)).collect();
+        self.best_matches(&word, |phonemes| {
+            let other = plain(phonemes);
+            sounds.iter()
+                .filter_map(|wanted| match phoneme_distance(wanted, &other) {
+                    0 => Some(Likeness::Same),
+                    1 => Some(Likeness::Close),
+                    _ => None,
+                })
+                .min()
+        })
+    }
+
+    /// Every other word with its best grade over all of its pronunciations.
+    fn best_matches<T: Ord + Copy>(&self, word: &str, grade: impl Fn(&[String]) -> Option<T>) -> Vec<(&str, T)> {
+        let mut matches: Vec<(&str, T)> = self.entries.iter()
+            .filter(|(other, _)| other.as_str() != word)
+            .filter_map(|(other, alternatives)| {
+                alternatives.iter().filter_map(|phonemes| grade(phonemes)).min().map(|grade| (other.as_str(), grade))
+            })
+            .collect();
+        matches.sort_by_key(|&(other, grade)| (grade, other));
+        matches
+    }
+}
+
+/// Whether an ARPAbet phoneme, with or without its stress mark, is a vowel.
+fn is_vowel(phoneme: &str) -> bool {
+    phoneme.starts_with(['A', 'E', 'I', 'O', 'U'])
+}
+
+/// Phonemes without their stress marks.
+fn plain(phonemes: &[String]) -> Vec<&str> {
+    phonemes.iter().map(|phoneme| phoneme.trim_end_matches(|c: char| c.is_ascii_digit())).collect()
+}
+
+/// Phonemes from the last stressed vowel on, or from the last vowel in unstressed words.
+fn rhyme_part(phonemes: &[String]) -> &[String] {
+    let stressed = phonemes.iter().rposition(|phoneme| phoneme.ends_with('1') || phoneme.ends_with('2'));
+    let start = stressed.or_else(|| phonemes.iter().rposition(|phoneme| is_vowel(phoneme)));
+    &phonemes[start.unwrap_or(0)..]
+}
+
+fn rhyme_quality(wanted: &[&str], other: &[&str]) -> Option<RhymeQuality> {
+    if wanted == other {
+        return Some(RhymeQuality::Perfect);
+    }
+    // Many speakers say the vowels of "cot" and "caught" alike.
+    let merge = |phonemes: &[&str]| -> Vec<String> {
+        phonemes.iter().map(|phoneme| if *phoneme == "AO" { "AA".to_string() } else { phoneme.to_string() }).collect()
+    };
+    let vowels = |phonemes: Vec<String>| -> Vec<String> {
+        phonemes.into_iter().filter(|phoneme| is_vowel(phoneme)).collect()
+    };
+    let last_syllable = |phonemes: &[&str]| -> Vec<String> {
+        let start = phonemes.iter().rposition(|phoneme| is_vowel(phoneme));
+        merge(&phonemes[start.unwrap_or(0)..])
+    };
+
+    let near = merge(wanted) == merge(other)
+        || vowels(merge(wanted)) == vowels(merge(other))
+        || (wanted.len() > 2 && last_syllable(wanted) == last_syllable(other));
+    near.then_some(RhymeQuality::Near)
+}
+
+/// Edits, in phonemes, needed to turn one pronunciation into the other.
+fn phoneme_distance(a: &[&str], b: &[&str]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, left) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, right) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left != right);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Syllables guessed from spelling: groups of vowels, not counting a silent final "e".
+pub fn estimate_syllables(word: &str) -> usize {
+    let word = word.trim().to_lowercase();
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let mut groups = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            groups += 1;
+        }
+        previous_vowel = vowel;
+    }
+    let silent_e = word.ends_with('e') && !word.ends_with("le") && !word.ends_with("ee") && groups > 1;
+    (groups - usize::from(silent_e)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words<T: Copy + PartialEq>(matches: &[(&str, T)], grade: T) -> Vec<String> {
+        matches.iter().filter(|(_, found)| *found == grade).map(|(word, _)| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_alternatives_and_comments() {
+        let pronunciations = Pronunciations::parse(";;; comment\nTOMATO  T AH0 M EY1 T OW2\ntomato(2) t ah0 m aa1 t ow2 # British\n");
+        assert_eq!(pronunciations.get("Tomato").len(), 2);
+        assert_eq!(pronunciations.get("tomato")[1][3], "AA1");
+        assert_eq!(pronunciations.syllables("tomato"), 3);
+    }
+
+    #[test]
+    fn test_rhymes_by_quality() {
+        let pronunciations = Pronunciations::parse(BUNDLED_CMUDICT);
+        let rhymes = pronunciations.rhymes("cat");
+        let perfect = words(&rhymes, RhymeQuality::Perfect);
+        assert!(perfect.contains(&"acrobat".to_string()) && perfect.contains(&"hat".to_string()));
+        assert!(!perfect.contains(&"cat".to_string()));
+        assert!(words(&rhymes, RhymeQuality::Near).contains(&"bad".to_string()));
+        assert_eq!(rhymes[0].1, RhymeQuality::Perfect);
+
+        assert!(words(&pronunciations.rhymes("dog"), RhymeQuality::Near).contains(&"fog".to_string()));
+        assert!(pronunciations.rhymes("zzyzx").is_empty());
+    }
+
+    #[test]
+    fn test_sounds_like() {
+        let pronunciations = Pronunciations::parse(BUNDLED_CMUDICT);
+        let sounds = pronunciations.sounds_like("write");
+        assert_eq!(words(&sounds, Likeness::Same), vec!["right", "rite"]);
+        assert!(words(&sounds, Likeness::Close).contains(&"light".to_string()));
+    }
+
+    #[test]
+    fn test_estimate_syllables() {
+        assert_eq!(estimate_syllables("cake"), 1);
+        assert_eq!(estimate_syllables("table"), 2);
+        assert_eq!(estimate_syllables("banana"), 3);
+        assert_eq!(estimate_syllables("rhythm"), 1);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use log::info;
+
+use crate::dictionary::glossary::Glossary;
+use crate::dictionary::wordnet::WordNet;
+use crate::idioms::Idioms;
+use crate::lemma::Lemmatizer;
+use crate::sentence::tokenize;
+
+/// Most candidate words returned for one description.
+pub const MAX_CANDIDATES: usize = 10;
+
+/// How quickly repeating a query term in a gloss stops raising its score.
+const K1: f64 = 1.2;
+
+/// How much long glosses are penalized.
+const B: f64 = 0.75;
+
+/// Words too common in glosses to tell them apart.
+const STOP_WORDS: [&str; 32] = [
+    "a", "an", "the", "of", "to", "in", "on", "for", "with", "by", "at", "from", "or", "and", "as", "is", "are", "be",
+    "that", "which", "who", "it", "its", "this", "something", "someone", "word", "thing", "what", "when", "used", "you",
+];
+
+/// Gloss of one sense and the words it defines.
+#[derive(Debug)]
+struct Gloss {
+    words: Vec<String>,
+    definition: String,
+    length: usize,
+}
+
+/// Word found for a description, with the definition that matched it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub word: String,
+    pub definition: String,
+    pub score: f64,
+}
+
+/// Full-text index over the glosses of the offline dictionaries, ranking them with BM25.
+#[derive(Debug, Default)]
+pub struct ReverseIndex {
+    glosses: Vec<Gloss>,
+    /// Glosses holding each term, with how often it appears in them.
+    postings: HashMap<String, Vec<(usize, usize)>>,
+    total_length: usize,
+}
+
+/// Terms of `text` worth matching: its words without stop words, as their lemma when inflected.
+fn terms(text: &str) -> Vec<String> {
+    tokenize(text).into_iter()
+        .filter(|token| !STOP_WORDS.contains(&token.as_str()) && token.chars().any(char::is_alphabetic))
+        .map(|token| Lemmatizer::global().lemmatize(&token).map_or(token, |lemma| lemma.lemma))
+        .collect()
+}
+
+impl ReverseIndex {
+    /// Indexes a gloss defining `words`.
+    pub fn add(&mut self, words: Vec<String>, definition: &str) {
+        let terms = terms(definition);
+        if words.is_empty() || terms.is_empty() {
+            return;
+        }
+        let id = self.glosses.len();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for term in &terms {
+            *counts.entry(term.clone()).or_default() += 1;
+        }
+        for (term, count) in counts {
+            self.postings.entry(term).or_default().push((id, count));
+        }
+        self.total_length += terms.len();
+        self.glosses.push(Gloss { words, definition: definition.to_string(), length: terms.len() });
+    }
+
+    /// Index over WordNet, the glossary and the bundled idioms.
+    pub fn global() -> &'static ReverseIndex {
+        static INDEX: OnceLock<ReverseIndex> = OnceLock::new();
+        INDEX.get_or_init(|| {
+            let mut index = ReverseIndex::default();
+            for synset in WordNet::global().all_synsets() {
+                index.add(synset.words.clone(), &synset.definition);
+            }
+            for (term, meanings) in Glossary::global().entries() {
+                for detail in meanings.iter().flat_map(|meaning| &meaning.definitions) {
+                    index.add(vec![term.to_string()], &detail.definition);
+                }
+            }
+            for idiom in Idioms::global().all() {
+                index.add(vec![idiom.phrase.clone()], &idiom.definition);
+            }
+            info!("Indexed {} glosses for reverse lookups", index.glosses.len());
+            index
+        })
+    }
+
+    /// Words whose glosses best match `description`, best first and each word once. Words of the
+    /// description itself are left out.
+    pub fn search(&self, description: &str, limit: usize) -> Vec<Candidate> {
+        let query: HashSet<String> = terms(description).into_iter().collect();
+        if query.is_empty() || self.glosses.is_empty() {
+            return Vec::new();
+        }
+
+        let count = self.glosses.len() as f64;
+        let average_length = self.total_length as f64 / count;
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for term in &query {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let matching = postings.len() as f64;
+            let idf = ((count - matching + 0.5) / (matching + 0.5) + 1.0).ln();
+            for &(id, frequency) in postings {
+                let frequency = frequency as f64;
+                let length = self.glosses[id].length as f64;
+                let weight = frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length / average_length));
+                *scores.entry(id).or_default() += idf * weight;
+            }
+        }
+
+        let mut ranked: Vec<(usize, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+
+        let asked: HashSet<String> = tokenize(description).into_iter().collect();
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for (id, score) in ranked {
+            let gloss = &self.glosses[id];
+            for word in &gloss.words {
+                let known = candidates.iter().any(|candidate| candidate.word.eq_ignore_ascii_case(word));
+                if !known && !asked.contains(&word.to_lowercase()) {
+                    candidates.push(Candidate { word: word.clone(), definition: gloss.definition.clone(), score });
+                }
+            }
+            if candidates.len() >= limit {
+                break;
+            }
+        }
+        candidates.truncate(limit);
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> ReverseIndex {
+        let mut index = ReverseIndex::default();
+        index.add(vec!["insomnia".to_string()], "chronic inability to sleep");
+        index.add(vec!["nap".to_string(), "catnap".to_string()], "a short sleep during the day");
+        index.add(vec!["sleepwalker".to_string()], "someone who walks while asleep");
+        index.add(vec!["umbrella".to_string()], "a device that keeps rain off a person");
+        index
+    }
+
+    #[test]
+    fn test_search_ranks_matching_glosses() {
+        let words: Vec<String> = index().search("unable to sleep at night", MAX_CANDIDATES).into_iter().map(|candidate| candidate.word).collect();
+        assert_eq!(words[0], "insomnia");
+        assert!(!words.contains(&"umbrella".to_string()));
+    }
+
+    #[test]
+    fn test_search_lists_synonyms_and_skips_asked_words() {
+        let candidates = index().search("short sleep in the afternoon, like a nap", MAX_CANDIDATES);
+        let words: Vec<&str> = candidates.iter().map(|candidate| candidate.word.as_str()).collect();
+        assert_eq!(words[0], "catnap");
+        assert!(!words.contains(&"nap"));
+        assert_eq!(candidates[0].definition, "a short sleep during the day");
+    }
+
+    #[test]
+    fn test_search_needs_meaningful_words() {
+        assert!(index().search("the of a", MAX_CANDIDATES).is_empty());
+        assert_eq!(index().search("walking", 1)[0].word, "sleepwalker");
+    }
+}
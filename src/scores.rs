@@ -0,0 +1,41 @@
+/// Points of each letter from a to z in English Scrabble.
+const SCRABBLE_POINTS: [u32; 26] = [1, 3, 3, 2, 1, 4, 2, 4, 1, 8, 5, 1, 3, 1, 1, 3, 10, 1, 1, 1, 1, 4, 4, 8, 4, 10];
+
+/// Points of each letter from a to z in Words with Friends.
+const WORDS_WITH_FRIENDS_POINTS: [u32; 26] = [1, 4, 4, 2, 1, 4, 3, 3, 1, 10, 5, 2, 4, 2, 1, 4, 10, 1, 1, 1, 2, 5, 4, 8, 3, 10];
+
+/// Scrabble score of `word` without premium squares. Letters other than a to z score nothing.
+pub fn scrabble(word: &str) -> u32 {
+    score(word, &SCRABBLE_POINTS)
+}
+
+/// Words with Friends score of `word` without premium squares.
+pub fn words_with_friends(word: &str) -> u32 {
+    score(word, &WORDS_WITH_FRIENDS_POINTS)
+}
+
+fn score(word: &str, points: &[u32; 26]) -> u32 {
+    word.chars()
+        .map(|c| c.to_ascii_lowercase())
+        .filter(char::is_ascii_lowercase)
+        .map(|c| points[usize::from(c as u8 - b'a')])
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrabble() {
+        assert_eq!(scrabble("quiz"), 22);
+        assert_eq!(scrabble("Cat"), 5);
+        assert_eq!(scrabble("café"), 8);
+    }
+
+    #[test]
+    fn test_words_with_friends() {
+        assert_eq!(words_with_friends("quiz"), 23);
+        assert_eq!(words_with_friends("cat"), 6);
+    }
+}
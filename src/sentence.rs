@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use futures::future::join_all;
+
+use crate::dictionary::glossary::Glossary;
+use crate::dictionary::wordnet::WordNet;
+use crate::idioms::Idioms;
+use crate::lookup::{lookup, Found, Source};
+use crate::settings::UrbanFilter;
+
+const COMMON_WORDS: &str = include_str!("sentence/common_words.txt");
+
+/// Most terms defined for one message, to keep both the reply and the number of requests small.
+pub const MAX_GLOSSARY_TERMS: usize = 12;
+
+/// Longest phrase, in words, looked for inside a sentence.
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// Definitions of the terms of a sentence, in the order they appear.
+#[derive(Default)]
+pub struct SentenceGlossary {
+    pub entries: Vec<Found>,
+    /// Terms no source knows.
+    pub missing: Vec<String>,
+}
+
+impl SentenceGlossary {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Words of `text` in lowercase, with typographic apostrophes made plain. Apostrophes and hyphens
+/// inside a word are kept, as in "rock'n'roll" or "well-known".
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’' || c == '-'))
+        .map(|token| token.replace('’', "'").trim_matches(|c| c == '\'' || c == '-').to_lowercase())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// Whether `text` holds more than one word.
+pub fn is_sentence(text: &str) -> bool {
+    tokenize(text).len() > 1
+}
+
+fn common_words() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        COMMON_WORDS.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).collect()
+    })
+}
+
+/// Terms worth defining in a sentence, in order and without repeats: known phrases first, longest
+/// match winning, then every other word except common ones, numbers and single letters.
+pub fn terms(tokens: &[String], is_phrase: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut start = 0;
+    while start < tokens.len() {
+        let longest = MAX_PHRASE_WORDS.min(tokens.len() - start);
+        let phrase = (2..=longest).rev()
+            .map(|length| (length, tokens[start..start + length].join(" ")))
+            .find(|(_, phrase)| is_phrase(phrase));
+
+        let (length, term) = match phrase {
+            Some(phrase) => phrase,
+            None => (1, tokens[start].clone()),
+        };
+        start += length;
+
+        let skipped = length == 1
+            && (common_words().contains(term.as_str()) || term.chars().count() < 2 || !term.chars().any(char::is_alphabetic));
+        if !skipped && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Sources of `chain` asked about a sentence or its words. Urban Dictionary is left out as it
+/// knows any phrase, and its slang senses of common words would crowd the glossary.
+pub fn chain(chain: &[Source]) -> Vec<Source> {
+    chain.iter().copied().filter(|source| *source != Source::Urban).collect()
+}
+
+/// Looks up every term of `text` at once through the sentence [`chain`] of `chain`.
+pub async fn define(text: &str, chain: &[Source], filter: UrbanFilter) -> SentenceGlossary {
+    let is_phrase = |phrase: &str| Glossary::global().contains(phrase) || Idioms::global().contains(phrase) || WordNet::global().contains(phrase);
+    let mut terms = terms(&tokenize(text), is_phrase);
+    terms.truncate(MAX_GLOSSARY_TERMS);
+
+    let chain = self::chain(chain);
+    let results = join_all(terms.iter().map(|term| lookup(term, &chain, filter))).await;
+    let mut glossary = SentenceGlossary::default();
+    for (term, result) in terms.into_iter().zip(results) {
+        match result {
+            Ok(found) => glossary.entries.push(found),
+            Err(_) => glossary.missing.push(term),
+        }
+    }
+    glossary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lookup::DEFAULT_CHAIN;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Don’t panic: it's a well-known 'fact', 42!"), strings(&["don't", "panic", "it's", "a", "well-known", "fact", "42"]));
+    }
+
+    #[test]
+    fn test_terms_skip_common_words_and_repeats() {
+        let tokens = tokenize("The cat and the other cat sat on a mat, 2 times");
+        assert_eq!(terms(&tokens, |_| false), strings(&["cat", "sat", "mat", "times"]));
+    }
+
+    #[test]
+    fn test_terms_prefer_longest_phrase() {
+        let phrases = ["yak shaving", "yak shaving day", "ice cream"];
+        let tokens = tokenize("Yak shaving day with ice cream");
+        assert_eq!(terms(&tokens, |phrase| phrases.contains(&phrase)), strings(&["yak shaving day", "ice cream"]));
+    }
+
+    #[test]
+    fn test_chain_leaves_out_urban() {
+        assert!(!chain(&DEFAULT_CHAIN).contains(&Source::Urban));
+        assert_eq!(chain(&[Source::Urban, Source::Glossary]), vec![Source::Glossary]);
+    }
+
+    #[test]
+    fn test_is_sentence() {
+        assert!(is_sentence("hello world"));
+        assert!(!is_sentence("  hello! "));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use teloxide::types::ChatId;
+
+use crate::telegram::RenderOptions;
+use crate::urban::{arrange, ContentFilter, NsfwPolicy, Rating, UrbanDefinition, UrbanSort};
+
+/// Senses shown before the rest of a part of speech is collapsed; off until a chat turns it on
+/// with /collapse.
+pub const DEFAULT_COLLAPSE_THRESHOLD: usize = 0;
+
+/// Random and daily urban entries remembered per chat so they are not shown twice.
+const RECENT_URBAN_LIMIT: usize = 100;
+
+/// Preferences a chat can change with bot commands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatSettings {
+    /// Parts of speech with more senses than this get all but the first one collapsed; 0 disables it.
+    pub collapse_threshold: usize,
+    pub urban_sort: UrbanSort,
+    /// Urban definitions with fewer net votes than this are hidden.
+    pub urban_min_score: Option<i64>,
+    /// Explicit content policy chosen by the chat, if any.
+    pub nsfw_policy: Option<NsfwPolicy>,
+    /// Ids of random and daily urban entries recently shown, oldest first.
+    pub recent_urban: Vec<u64>,
+    /// Show syllables, frequency and level under the header of definitions.
+    pub show_metrics: bool,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        ChatSettings {
+            collapse_threshold: DEFAULT_COLLAPSE_THRESHOLD,
+            urban_sort: UrbanSort::default(),
+            urban_min_score: None,
+            nsfw_policy: None,
+            recent_urban: Vec::new(),
+            show_metrics: false,
+        }
+    }
+}
+
+impl ChatSettings {
+    pub fn nsfw_policy(&self, is_private: bool) -> NsfwPolicy {
+        self.nsfw_policy.unwrap_or_else(|| NsfwPolicy::default_for(is_private))
+    }
+
+    pub fn urban_filter(&self, is_private: bool) -> UrbanFilter {
+        UrbanFilter {
+            sort: self.urban_sort,
+            min_score: self.urban_min_score,
+            hide_explicit: self.nsfw_policy(is_private) == NsfwPolicy::Hide,
+        }
+    }
+
+    /// Records an urban entry as shown, forgetting the oldest one past the limit.
+    pub fn remember_urban(&mut self, defid: u64) {
+        self.recent_urban.retain(|&seen| seen != defid);
+        self.recent_urban.push(defid);
+        if self.recent_urban.len() > RECENT_URBAN_LIMIT {
+            self.recent_urban.remove(0);
+        }
+    }
+
+    pub fn render_options(&self, bot_username: &str, is_private: bool) -> RenderOptions {
+        RenderOptions {
+            collapse_threshold: self.collapse_threshold,
+            bot_username: Some(bot_username.to_string()),
+            mask_offensive: self.nsfw_policy(is_private) == NsfwPolicy::Mask,
+            show_metrics: self.show_metrics,
+        }
+    }
+}
+
+/// Chat preferences deciding which urban definitions are shown, and in which order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UrbanFilter {
+    pub sort: UrbanSort,
+    pub min_score: Option<i64>,
+    pub hide_explicit: bool,
+}
+
+impl UrbanFilter {
+    pub fn apply(&self, definitions: Vec<UrbanDefinition>) -> Vec<UrbanDefinition> {
+        let mut definitions = arrange(definitions, self.sort, self.min_score);
+        if self.hide_explicit {
+            definitions.retain(|definition| ContentFilter::global().classify(definition) == Rating::Clean);
+        }
+        definitions
+    }
+}
+
+/// In-memory settings of every chat, shared between handlers.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    chats: Arc<RwLock<HashMap<ChatId, ChatSettings>>>,
+}
+
+impl Settings {
+    pub fn get(&self, chat_id: ChatId) -> ChatSettings {
+        self.chats.read().unwrap().get(&chat_id).cloned().unwrap_or_default()
+    }
+
+    pub fn update(&self, chat_id: ChatId, change: impl FnOnce(&mut ChatSettings)) {
+        let mut chats = self.chats.write().unwrap();
+        change(chats.entry(chat_id).or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_chat_gets_defaults() {
+        let settings = Settings::default();
+        assert_eq!(settings.get(ChatId(1)), ChatSettings::default());
+    }
+
+    #[test]
+    fn test_nsfw_policy_defaults_by_chat_type() {
+        let mut chat = ChatSettings::default();
+        assert!(!chat.render_options("wordbot", true).mask_offensive);
+        assert!(chat.render_options("wordbot", false).mask_offensive);
+
+        chat.nsfw_policy = Some(NsfwPolicy::Allow);
+        assert!(!chat.render_options("wordbot", false).mask_offensive);
+    }
+
+    #[test]
+    fn test_urban_filter_hides_explicit_definitions() {
+        let mut chat = ChatSettings { nsfw_policy: Some(NsfwPolicy::Hide), ..Default::default() };
+        assert!(chat.urban_filter(true).hide_explicit);
+
+        chat.nsfw_policy = None;
+        assert!(!chat.urban_filter(false).hide_explicit);
+    }
+
+    #[test]
+    fn test_remember_urban_keeps_most_recent() {
+        let mut chat = ChatSettings::default();
+        for defid in 0..RECENT_URBAN_LIMIT as u64 + 5 {
+            chat.remember_urban(defid);
+        }
+        chat.remember_urban(10);
+
+        assert_eq!(chat.recent_urban.len(), RECENT_URBAN_LIMIT);
+        assert_eq!(chat.recent_urban.first(), Some(&5));
+        assert_eq!(chat.recent_urban.last(), Some(&10));
+    }
+
+    #[test]
+    fn test_update_only_changes_given_chat() {
+        let settings = Settings::default();
+        settings.update(ChatId(1), |chat| chat.collapse_threshold = 3);
+
+        assert_eq!(settings.get(ChatId(1)).collapse_threshold, 3);
+        assert_eq!(settings.get(ChatId(2)).collapse_threshold, DEFAULT_COLLAPSE_THRESHOLD);
+    }
+}
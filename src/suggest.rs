@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use crate::words::WordList;
+
+/// Most edits between a typo and a word suggested for it.
+const MAX_EDITS: usize = 2;
+
+/// Words suggested when a lookup finds nothing.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Longer input is not a typo of a listed word and is not worth the search.
+const MAX_INPUT_LENGTH: usize = 32;
+
+const KEYBOARD_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Costs are counted in half edits, so hitting a key next to the right one costs half a typo.
+const EDIT_COST: usize = 2;
+const ADJACENT_KEY_COST: usize = 1;
+
+/// Symmetric delete index over a word list: every word is stored under each string obtained by
+/// deleting up to [`MAX_EDITS`] of its letters, so candidates for a typo are found by deleting
+/// letters from the typo instead of comparing it with every word.
+#[derive(Debug, Default)]
+pub struct Suggester {
+    words: Vec<String>,
+    deletes: HashMap<String, Vec<usize>>,
+}
+
+impl Suggester {
+    pub fn new(words: &[String]) -> Self {
+        let mut suggester = Suggester::default();
+        for (index, word) in words.iter().enumerate() {
+            for delete in deletes(word) {
+                suggester.deletes.entry(delete).or_default().push(index);
+            }
+            suggester.words.push(word.clone());
+        }
+        suggester
+    }
+
+    /// Index over the shared [`WordList`].
+    pub fn global() -> &'static Suggester {
+        static SUGGESTER: OnceLock<Suggester> = OnceLock::new();
+        SUGGESTER.get_or_init(|| Suggester::new(WordList::global().words()))
+    }
+
+    /// Up to `limit` listed words close to `input`, closest first.
+    pub fn suggest(&self, input: &str, limit: usize) -> Vec<&str> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() || input.chars().count() > MAX_INPUT_LENGTH {
+            return Vec::new();
+        }
+
+        let candidates: HashSet<usize> = deletes(&input).iter()
+            .filter_map(|delete| self.deletes.get(delete))
+            .flatten()
+            .copied()
+            .collect();
+
+        let mut ranked: Vec<(usize, usize, &str)> = candidates.into_iter()
+            .map(|index| self.words[index].as_str())
+            .filter(|word| *word != input)
+            .map(|word| (distance(&input, word), word.len().abs_diff(input.len()), word))
+            .filter(|(cost, _, _)| *cost <= MAX_EDITS * EDIT_COST)
+            .collect();
+        ranked.sort();
+        ranked.into_iter().take(limit).map(|(_, _, word)| word).collect()
+    }
+}
+
+/// The word itself and every string left after deleting up to [`MAX_EDITS`] of its letters.
+fn deletes(word: &str) -> HashSet<String> {
+    let mut found = HashSet::from([word.to_string()]);
+    let mut frontier = vec![word.to_string()];
+    for _ in 0..MAX_EDITS {
+        let mut next = Vec::new();
+        for current in &frontier {
+            let chars: Vec<char> = current.chars().collect();
+            for skip in 0..chars.len() {
+                let delete: String = chars.iter().enumerate().filter(|(i, _)| *i != skip).map(|(_, c)| c).collect();
+                if found.insert(delete.clone()) {
+                    next.push(delete);
+                }
+            }
+        }
+        frontier = next;
+    }
+    found
+}
+
+fn key_position(key: char) -> Option<(usize, usize)> {
+    KEYBOARD_ROWS.iter().enumerate().find_map(|(row, keys)| keys.find(key).map(|column| (row, column)))
+}
+
+fn adjacent_keys(a: char, b: char) -> bool {
+    match (key_position(a), key_position(b)) {
+        (Some((row_a, column_a)), Some((row_b, column_b))) => {
+            a != b && row_a.abs_diff(row_b) <= 1 && column_a.abs_diff(column_b) <= 1
+        }
+        _ => false,
+    }
+}
+
+/// Optimal string alignment distance in half edits, where swapping two neighbouring letters is
+/// a single edit and typing a key adjacent to the intended one is half an edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i * EDIT_COST;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j * EDIT_COST;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = match (a[i - 1], b[j - 1]) {
+                (x, y) if x == y => 0,
+                (x, y) if adjacent_keys(x, y) => ADJACENT_KEY_COST,
+                _ => EDIT_COST,
+            };
+            let mut cost = (table[i - 1][j] + EDIT_COST)
+                .min(table[i][j - 1] + EDIT_COST)
+                .min(table[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cost = cost.min(table[i - 2][j - 2] + EDIT_COST);
+            }
+            table[i][j] = cost;
+        }
+    }
+    table[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggester() -> Suggester {
+        let words: Vec<String> = ["cat", "cut", "cost", "hello", "help", "the", "they", "receive"]
+            .iter().map(|word| word.to_string()).collect();
+        Suggester::new(&words)
+    }
+
+    #[test]
+    fn test_suggests_close_words() {
+        assert_eq!(suggester().suggest("helo", MAX_SUGGESTIONS), vec!["help", "hello"]);
+        assert_eq!(suggester().suggest("recieve", MAX_SUGGESTIONS), vec!["receive"]);
+    }
+
+    #[test]
+    fn test_transposition_is_one_edit() {
+        assert_eq!(distance("teh", "the"), EDIT_COST);
+        assert_eq!(suggester().suggest("teh", 1), vec!["the"]);
+    }
+
+    #[test]
+    fn test_adjacent_keys_rank_first() {
+        assert_eq!(distance("cst", "cat"), ADJACENT_KEY_COST);
+        assert_eq!(suggester().suggest("cst", 3), vec!["cat", "cut", "cost"]);
+    }
+
+    #[test]
+    fn test_no_suggestions_for_distant_or_empty_input() {
+        assert!(suggester().suggest("xylophone", MAX_SUGGESTIONS).is_empty());
+        assert!(suggester().suggest("  ", MAX_SUGGESTIONS).is_empty());
+    }
+
+    #[test]
+    fn test_bundled_list_suggests() {
+        assert!(Suggester::global().suggest("definately", MAX_SUGGESTIONS).contains(&"definitely"));
+    }
+}
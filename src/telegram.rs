@@ -1,25 +1,29 @@
-use teloxide::types::Message;
-use crate::dictionary::Definition;
-use crate::urban::UrbanDefinition;
+use teloxide::types::{InlineKeyboardMarkup, Message};
+use crate::anagram::Anagrams;
+use crate::dictionary::wiktionary::Translation;
+use crate::dictionary::{Definition, DefinitionDetail};
+use crate::discovery::WordGroups;
+use crate::etymology::{highlight, Etymology, Span};
+use crate::pattern::Matches;
+use crate::reverse::Candidate;
+use crate::lexicon::{Validity, Verdict};
+use crate::lookup::Answer;
+use crate::metrics::{FrequencyList, WordMetrics};
+use crate::pronunciation::Pronunciations;
+use crate::sentence::SentenceGlossary;
+use crate::thesaurus::RelatedWords;
+use crate::urban::{parse_references, ContentFilter, Segment, UrbanDefinition};
+use crate::words::WordList;
 
-pub trait Sanitize {
-    fn sanitize(&self) -> String;
-}
+mod chunk;
+mod deep_link;
+mod document;
+mod html;
+mod markdown;
 
-impl Sanitize for String {
-    fn sanitize(&self) -> String {
-        let special_chars = [
-            "[", "]", "(", ")", ">", "#", "+", "-", "=", "|", "{", "}", ".", "!", "_", "*", "~", "`",
-        ];
-        let mut sanitized = self.clone();
-
-        for c in &special_chars {
-            sanitized = sanitized.replace(*c, &format!("\\{}", c));
-        }
-
-        sanitized
-    }
-}
+pub use chunk::{split_message, MAX_MESSAGE_LENGTH};
+pub use deep_link::StartPayload;
+pub use document::{Document, Format};
 
 pub trait LogFormat {
     fn format_for_log(&self) -> String;
@@ -43,22 +47,46 @@ impl LogFormat for Message {
 }
 
 
-pub trait Telegram {
-    fn to_message(&self) -> String {
-        let mut message = String::new();
-        message.push_str(&self.build_message());
-        message
+/// Message to send, with buttons shown under its last chunk.
+#[derive(Debug, Clone, Default)]
+pub struct Reply {
+    pub document: Document,
+    pub keyboard: Option<InlineKeyboardMarkup>,
+}
+
+impl From<Document> for Reply {
+    fn from(document: Document) -> Self {
+        Reply { document, keyboard: None }
     }
+}
+
+/// Per chat choices that change how entries are rendered.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Parts of speech with more senses than this show only the first one expanded; 0 disables it.
+    pub collapse_threshold: usize,
+    /// Username of the bot, needed to turn urban cross references into deep links.
+    pub bot_username: Option<String>,
+    /// Hide offensive words of urban content behind spoilers.
+    pub mask_offensive: bool,
+    /// Show syllables, frequency and level under the header of definitions.
+    pub show_metrics: bool,
+}
 
-    fn build_message(&self) -> String;
+pub trait Telegram {
+    fn build_message(&self, options: &RenderOptions) -> Document;
 }
 
 impl Telegram for Vec<Definition> {
-    fn build_message(&self) -> String {
-        let mut message = String::new();
-        for (_, definition) in self.iter().enumerate() {
-            message.push_str(&format!("*Definitions for* _{}_:\n", definition.word.sanitize()));
-            message.push_str(&definition.build_message());
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        for definition in self.iter() {
+            message.bold("Definitions for").text(" ").italic(&definition.word).text(":").newline();
+            if options.show_metrics {
+                let metrics = FrequencyList::global().metrics(&definition.word, WordList::global(), Pronunciations::global());
+                message.italic(&metrics_summary(&metrics)).newline();
+            }
+            message.append(&definition.build_message(options));
         }
         message
     }
@@ -66,94 +94,378 @@ impl Telegram for Vec<Definition> {
 
 
 impl Telegram for Definition {
-    fn build_message(&self) -> String {
-        let mut message = String::new();
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
         for meaning in &self.meanings {
-            message.push_str(&format!("*[{}]*\n", meaning.part_of_speech.sanitize()));
-            for definition in &meaning.definitions {
-                message.push_str(&format!("\\- {}\n", definition.definition.sanitize()));
+            message.bold(&format!("[{}]", meaning.part_of_speech));
+            if !meaning.inflections.is_empty() {
+                message.text(" ").italic(&meaning.inflections.join(", "));
+            }
+            message.newline();
+
+            let collapse = options.collapse_threshold > 0 && meaning.definitions.len() > options.collapse_threshold;
+            let shown = if collapse { 1 } else { meaning.definitions.len() };
+            for definition in &meaning.definitions[..shown] {
+                message.text(&format!("- {}", definition.definition)).newline();
+                if let Some(example) = &definition.example {
+                    message.italic(&format!("\"{}\"", example)).newline();
+                }
+                if let Some(relations) = relations(definition) {
+                    message.italic(&relations).newline();
+                }
+            }
+
+            if collapse {
+                let hidden: Vec<String> = meaning.definitions[shown..].iter()
+                    .map(|definition| {
+                        let mut lines = vec![format!("- {}", definition.definition)];
+                        lines.extend(definition.example.iter().map(|example| format!("\"{}\"", example)));
+                        lines.extend(relations(definition));
+                        lines.join("\n")
+                    })
+                    .collect();
+                message.expandable_blockquote(&hidden.join("\n"));
+            }
+            message.newline();
+        }
+        message
+    }
+}
+
+/// Broader and narrower terms of a sense, as in "kind of canine; such as puppy, hunting dog".
+fn relations(definition: &DefinitionDetail) -> Option<String> {
+    let mut parts = Vec::new();
+    if !definition.hypernyms.is_empty() {
+        parts.push(format!("kind of {}", definition.hypernyms.join(", ")));
+    }
+    if !definition.hyponyms.is_empty() {
+        parts.push(format!("such as {}", definition.hyponyms.join(", ")));
+    }
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// Syllables of a word, as in "1 syllable".
+fn syllable_count(syllables: usize) -> String {
+    format!("{} syllable{}", syllables, if syllables == 1 { "" } else { "s" })
+}
+
+/// One line of syllables, frequency band and level, as in "2 syllables · common · B1".
+fn metrics_summary(metrics: &WordMetrics) -> String {
+    format!("{} · {} · {}", syllable_count(metrics.syllables), metrics.band.label(), metrics.level)
+}
+
+impl Telegram for WordMetrics {
+    fn build_message(&self, _options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.bold("Level of").text(" ").italic(&self.word).text(":").newline();
+        message.bold("Syllables:").text(&format!(" {}", self.syllables)).newline();
+        message.bold("Frequency:").text(" ").text(self.band.label());
+        if let Some(rank) = self.rank {
+            message.text(&format!(" (rank {})", rank));
+        }
+        message.newline();
+        message.bold("CEFR:").text(&format!(" {} (estimated)", self.level)).newline();
+        message
+    }
+}
+
+impl Telegram for WordGroups {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.bold(self.title).text(" ").italic(&self.word).text(&format!(" ({}):", syllable_count(self.syllables))).newline();
+        for (label, words) in &self.groups {
+            message.bold(&format!("[{}]", label)).text(" ");
+            for (index, (word, syllables)) in words.iter().enumerate() {
+                if index > 0 {
+                    message.text(", ");
+                }
+                lookup_link(&mut message, word, options);
+                message.text(&format!(" ({})", syllables));
+            }
+            message.newline();
+        }
+        message
+    }
+}
+
+impl Telegram for Anagrams {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.bold("Anagrams of").text(" ").italic(&self.letters).text(" (Scrabble points):").newline();
+        for (label, words) in &self.groups {
+            message.bold(&format!("[{}]", label)).text(" ");
+            for (index, (word, score)) in words.iter().enumerate() {
+                if index > 0 {
+                    message.text(", ");
+                }
+                lookup_link(&mut message, word, options);
+                message.text(&format!(" ({})", score));
+            }
+            message.newline();
+        }
+        message
+    }
+}
+
+impl Telegram for Validity {
+    fn build_message(&self, _options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.bold(&self.word).text(":").newline();
+        for (name, verdict) in &self.lists {
+            let verdict = match verdict {
+                Verdict::Valid => " valid",
+                Verdict::NotValid => " not valid",
+                Verdict::NotInExcerpt => " not in bundled excerpt",
+            };
+            message.bold(&format!("[{}]", name)).text(verdict).newline();
+        }
+        message.text(&format!("Scrabble: {} points, Words with Friends: {} points", self.scrabble, self.words_with_friends)).newline();
+        message
+    }
+}
+
+impl Telegram for Matches {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.bold("Words matching").text(" ").italic(&self.query).text(&format!(" ({}):", self.total)).newline();
+        for (index, word) in self.words.iter().enumerate() {
+            if index > 0 {
+                message.text(", ");
+            }
+            lookup_link(&mut message, word, options);
+        }
+        message.newline();
+        if self.pages > 1 {
+            message.italic(&format!("Page {} of {}", self.page + 1, self.pages)).newline();
+        }
+        message
+    }
+}
+
+/// Etymology with its languages in bold and its dates in italics.
+impl Telegram for Etymology {
+    fn build_message(&self, _options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.bold("Origin of").text(" ").italic(&self.word).text(":").newline();
+        for span in highlight(&self.text) {
+            match span {
+                Span::Text(text) => message.text(text),
+                Span::Language(language) => message.bold(language),
+                Span::Date(date) => message.italic(date),
+            };
+        }
+        message.newline().italic(&format!("From {}", self.source.label())).newline();
+        message
+    }
+}
+
+/// Translations grouped by language, or by sense when they are all in one language.
+impl Telegram for Vec<Translation> {
+    fn build_message(&self, _options: &RenderOptions) -> Document {
+        let one_language = self.iter().all(|translation| translation.lang == self[0].lang);
+        let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+        for translation in self {
+            let key = match &translation.sense {
+                Some(sense) if one_language => sense.as_str(),
+                _ => translation.lang.as_str(),
+            };
+            match groups.iter_mut().find(|(group, _)| *group == key) {
+                Some((_, words)) if !words.contains(&translation.word.as_str()) => words.push(&translation.word),
+                Some(_) => {}
+                None => groups.push((key, vec![&translation.word])),
             }
-            message.push_str("\n");
+        }
+
+        let mut message = Document::new();
+        for (key, words) in groups {
+            message.bold(&format!("[{}]", key)).text(" ").text(&words.join(", ")).newline();
         }
         message
     }
 }
 
 impl Telegram for Vec<UrbanDefinition> {
-    fn build_message(&self) -> String {
-        let mut message = String::new();
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
         let custom = if self.len() > 1 { "s" } else { "" };
-        message.push_str(&format!("Found {} urban definition{} for *{}*\n", self.len(), custom, self[0].word.sanitize()));
-        for (_, definition) in self.iter().enumerate() {
-            message.push_str(&definition.build_message());
+        message.text(&format!("Found {} urban definition{} for ", self.len(), custom));
+        if options.mask_offensive && !ContentFilter::global().offensive_ranges(&self[0].word).is_empty() {
+            message.spoiler(&self[0].word);
+        } else {
+            message.bold(&self[0].word);
+        }
+        message.newline();
+        for definition in self.iter() {
+            message.append(&definition.build_message(options));
         }
         message
     }
 }
 
-impl Telegram for UrbanDefinition {
-    fn build_message(&self) -> String {
-        let mut message = String::new();
-        message.push_str(&format!("\n*Definition:*\n{}\n", self.definition.sanitize()));
-        if !self.example.is_empty() {
-            message.push_str(&format!("*Example:*\n{}\n", self.example.sanitize()));
+/// Adds plain urban text, hiding offensive words behind spoilers when the chat asks for it.
+fn masked_text(message: &mut Document, text: &str, options: &RenderOptions) {
+    if !options.mask_offensive {
+        message.text(text);
+        return;
+    }
+
+    let mut last = 0;
+    for range in ContentFilter::global().offensive_ranges(text) {
+        message.text(&text[last..range.start]).spoiler(&text[range.clone()]);
+        last = range.end;
+    }
+    message.text(&text[last..]);
+}
+
+/// Adds urban text, linking every `[term]` to a new lookup of that term when possible.
+fn urban_text(text: &str, options: &RenderOptions) -> Document {
+    let mut message = Document::new();
+    for segment in parse_references(text) {
+        match segment {
+            Segment::Text(text) => masked_text(&mut message, text, options),
+            Segment::Reference(term) => {
+                let offensive = options.mask_offensive && !ContentFilter::global().offensive_ranges(term).is_empty();
+                match options.bot_username.as_deref().and_then(|bot| StartPayload::Urban(term.to_string()).link(bot)) {
+                    _ if offensive => message.spoiler(term),
+                    Some(url) => message.link(term, &url),
+                    None => message.text(term),
+                };
+            }
+        }
+    }
+    message
+}
+
+/// Adds `term`, linked to a lookup of it when the bot username is known.
+fn lookup_link(message: &mut Document, term: &str, options: &RenderOptions) {
+    match options.bot_username.as_deref().and_then(|bot| StartPayload::Info(term.to_string()).link(bot)) {
+        Some(url) => message.link(term, &url),
+        None => message.text(term),
+    };
+}
+
+impl Telegram for RelatedWords {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        let title = self.relation.label();
+        message.bold(&format!("{}{} of", title[..1].to_uppercase(), &title[1..])).text(" ").italic(&self.word).text(":").newline();
+        for (part_of_speech, words) in &self.groups {
+            message.bold(&format!("[{}]", part_of_speech)).text(" ");
+            for (index, word) in words.iter().enumerate() {
+                if index > 0 {
+                    message.text(", ");
+                }
+                lookup_link(&mut message, word, options);
+            }
+            message.newline();
         }
-        message.push_str(&format!("*Author:*\n{}\n", self.author.sanitize()));
         message
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::dictionary::{DefinitionDetail, Meaning};
-    use super::*;
+/// Longest definition, in characters, shown for a term of a sentence.
+const SUMMARY_LENGTH: usize = 160;
 
-    #[test]
-    fn test_sanitize() {
-        let original = "hello-world!".to_string();
-        let expected = "hello\\-world\\!".to_string();
-        let sanitized = original.sanitize();
-        assert_eq!(sanitized, expected);
+/// Cuts `text` to its first line and at most [`SUMMARY_LENGTH`] characters.
+fn summary(text: &str) -> String {
+    let line = text.lines().find(|line| !line.trim().is_empty()).unwrap_or("").trim();
+    match line.char_indices().nth(SUMMARY_LENGTH) {
+        Some((end, _)) => format!("{}…", line[..end].trim_end()),
+        None => line.to_string(),
     }
+}
 
-    #[test]
-    fn test_sanitize_with_special_chars() {
-        let original = "####()!!".to_string();
-        let expected = "\\#\\#\\#\\#\\(\\)\\!\\!".to_string();
-        let sanitized = original.sanitize();
-        assert_eq!(sanitized, expected);
+/// Candidate words of a reverse lookup, numbered, each with the definition that matched.
+impl Telegram for Vec<Candidate> {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        for (index, candidate) in self.iter().enumerate() {
+            message.text(&format!("{}. ", index + 1));
+            lookup_link(&mut message, &candidate.word, options);
+            message.text(" — ").text(&summary(&candidate.definition)).newline();
+        }
+        message
     }
+}
 
-    #[test]
-    fn test_sanitize_with_no_special_chars() {
-        let original = "hello world".to_string();
-        let expected = original.clone();
-        let sanitized = original.sanitize();
-        assert_eq!(sanitized, expected);
+impl Telegram for SentenceGlossary {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        for found in &self.entries {
+            message.bold(&found.term);
+            if let Some(lemma) = &found.lemma {
+                message.text(&format!(" → {}", lemma.lemma));
+            }
+            message.text(" — ");
+            match &found.answer {
+                Answer::Definitions(defs) => {
+                    let meaning = defs.iter().flat_map(|definition| &definition.meanings).find(|meaning| !meaning.definitions.is_empty());
+                    if let Some(meaning) = meaning {
+                        message.italic(&meaning.part_of_speech).text(" ").text(&summary(&meaning.definitions[0].definition));
+                    }
+                }
+                Answer::Urban(defs) => {
+                    message.italic("urban").text(" ").append(&urban_text(&summary(&defs[0].definition), options));
+                }
+            }
+            message.newline();
+        }
+        if !self.missing.is_empty() {
+            message.italic(&format!("No definition found for: {}", self.missing.join(", "))).newline();
+        }
+        message
     }
+}
+
+impl Telegram for UrbanDefinition {
+    fn build_message(&self, options: &RenderOptions) -> Document {
+        let mut message = Document::new();
+        message.newline().bold("Definition:").newline().append(&urban_text(&self.definition, options)).newline();
+        if !self.example.is_empty() {
+            message.bold("Example:").newline().append(&urban_text(&self.example, options)).newline();
+        }
+        message.bold("Author:").newline().text(&self.author);
+        if !self.written_on.is_empty() {
+            message.text(&format!(" · {}", self.date()));
+        }
+        message.newline().text(&format!("👍 {}  👎 {}", self.thumbs_up, self.thumbs_down)).newline();
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::{DefinitionDetail, Meaning};
+    use super::*;
 
     #[test]
     fn test_to_message() {
-        let defs = vec![Definition {
+        let defs = [Definition {
             word: "test".to_string(),
             meanings: vec![
                 Meaning {
                     part_of_speech: "noun".to_string(),
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
                     definitions: vec![
                         DefinitionDetail {
                             definition: "a procedure intended to establish the quality, performance, or reliability of something, especially before it is taken into widespread use".to_string(),
                             antonyms: Some(vec!["flunk".to_string()]),
                             synonyms: Some(vec!["exam".to_string(), "trial".to_string()]),
                             example: Some("this is only a test".to_string()),
+                            hypernyms: Vec::new(),
+                            hyponyms: Vec::new(),
                         },
                     ],
                 }
             ],
+            origin: None,
         }];
 
-        let expected = "*[noun]*\n\\- a procedure intended to establish the quality, performance, or reliability of something, especially before it is taken into widespread use\n\n".to_string();
+        let expected = "*\\[noun\\]*\n\\- a procedure intended to establish the quality, performance, or reliability of something, especially before it is taken into widespread use\n_\"this is only a test\"_\n\n".to_string();
 
-        assert_eq!(defs[0].to_message(), expected);
+        assert_eq!(defs[0].build_message(&RenderOptions::default()).render(Format::MarkdownV2), expected);
     }
 
     #[test]
@@ -164,44 +476,536 @@ mod tests {
                 meanings: vec![
                     Meaning {
                         part_of_speech: "noun".to_string(),
+                        synonyms: Vec::new(),
+                        antonyms: Vec::new(),
+                        inflections: Vec::new(),
                         definitions: vec![
                             DefinitionDetail {
                                 definition: "a thing characteristic of its kind or illustrating a general rule".to_string(),
                                 antonyms: Some(vec!["flunk".to_string()]),
                                 synonyms: Some(vec!["exam".to_string(), "trial".to_string()]),
                                 example: Some("this is only a test".to_string()),
+                                hypernyms: Vec::new(),
+                                hyponyms: Vec::new(),
                             }
                         ],
                     }
                 ],
+                origin: None,
             },
             Definition {
                 word: "test".to_string(),
                 meanings: vec![
                     Meaning {
                         part_of_speech: "verb".to_string(),
+                        synonyms: Vec::new(),
+                        antonyms: Vec::new(),
+                        inflections: Vec::new(),
                         definitions: vec![
                             DefinitionDetail {
                                 definition: "take measures to check the quality, performance, or reliability of (something), especially before putting it into widespread use or practice".to_string(),
                                 antonyms: Some(vec!["flunk".to_string()]),
                                 synonyms: Some(vec!["exam".to_string(), "trial".to_string()]),
                                 example: Some("this is only a test".to_string()),
+                                hypernyms: Vec::new(),
+                                hyponyms: Vec::new(),
                             },
                         ],
                     }
                 ],
+                origin: None,
             },
         ];
 
         let expected_output = "\
             *Definitions for* _example_:\n\
-            *[noun]*\n\\- a thing characteristic of its kind or illustrating a general rule\n\
+            *\\[noun\\]*\n\\- a thing characteristic of its kind or illustrating a general rule\n\
+            _\"this is only a test\"_\n\
             \n\
             *Definitions for* _test_:\n\
-            *[verb]*\n\\- take measures to check the quality, performance, or reliability of \\(something\\), especially before putting it into widespread use or practice\n\
+            *\\[verb\\]*\n\\- take measures to check the quality, performance, or reliability of \\(something\\), especially before putting it into widespread use or practice\n\
+            _\"this is only a test\"_\n\
+            \n\
+        ";
+
+        assert_eq!(definitions.build_message(&RenderOptions::default()).render(Format::MarkdownV2), expected_output);
+    }
+
+    #[test]
+    fn test_telegram_trait_impl_for_vec_urban_definition() {
+        let definitions = vec![UrbanDefinition {
+            word: "wheelsucker".to_string(),
+            definition: "A cyclist who stays behind other cyclists' wheels.".to_string(),
+            example: "George is a wheelsucker\\!".to_string(),
+            author: "JKu".to_string(),
+            permalink: "https://www.urbandictionary.com/define.php?term=wheelsucker&defid=1766772".to_string(),
+            defid: 1766772,
+            thumbs_up: 120,
+            thumbs_down: 14,
+            written_on: "2006-05-31T00:00:00.000Z".to_string(),
+        }];
+
+        let expected_output = "\
+            Found 1 urban definition for *wheelsucker*\n\
             \n\
+            *Definition:*\nA cyclist who stays behind other cyclists' wheels\\.\n\
+            *Example:*\nGeorge is a wheelsucker\\\\\\!\n\
+            *Author:*\nJKu · 2006\\-05\\-31\n\
+            👍 120  👎 14\n\
         ";
 
-        assert_eq!(definitions.to_message(), expected_output);
+        assert_eq!(definitions.build_message(&RenderOptions::default()).render(Format::MarkdownV2), expected_output);
+    }
+
+    fn definitions_fixture() -> Vec<Definition> {
+        vec![Definition {
+            word: "set".to_string(),
+            meanings: vec![
+                Meaning {
+                    part_of_speech: "verb".to_string(),
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                    definitions: vec![
+                        DefinitionDetail {
+                            definition: "To put (something) down, to rest.".to_string(),
+                            antonyms: None,
+                            synonyms: Some(vec!["place".to_string()]),
+                            example: Some("Set the glass here.".to_string()),
+                            hypernyms: Vec::new(),
+                            hyponyms: Vec::new(),
+                        },
+                        DefinitionDetail {
+                            definition: "To determine; to settle <a date> & agree.".to_string(),
+                            antonyms: None,
+                            synonyms: None,
+                            example: None,
+                            hypernyms: Vec::new(),
+                            hyponyms: Vec::new(),
+                        },
+                    ],
+                },
+                Meaning {
+                    part_of_speech: "noun".to_string(),
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                    definitions: vec![
+                        DefinitionDetail {
+                            definition: "A collection of various objects; e.g. {1, 2} \\ [a-z]*.".to_string(),
+                            antonyms: None,
+                            synonyms: None,
+                            example: None,
+                            hypernyms: Vec::new(),
+                            hyponyms: Vec::new(),
+                        },
+                    ],
+                },
+            ],
+            origin: None,
+        }]
+    }
+
+    fn urban_fixture() -> Vec<UrbanDefinition> {
+        vec![
+            UrbanDefinition {
+                word: "wheelsucker".to_string(),
+                definition: "A [road cyclist] who stays behind other cyclists' wheels so that he/[she can] draft behind them.".to_string(),
+                example: "George is [the worst] wheelsucker of all!\n\"Be at the front\" <sometimes> & smile_".to_string(),
+                author: "JKu".to_string(),
+                permalink: "https://www.urbandictionary.com/define.php?term=wheelsucker&defid=1766772".to_string(),
+                defid: 1766772,
+                thumbs_up: 120,
+                thumbs_down: 14,
+                written_on: "2006-05-31T00:00:00.000Z".to_string(),
+            },
+            UrbanDefinition {
+                word: "wheelsucker".to_string(),
+                definition: "Someone who drafts (a lot).".to_string(),
+                example: "".to_string(),
+                author: "*rider*".to_string(),
+                permalink: "https://www.urbandictionary.com/define.php?term=wheelsucker&defid=2".to_string(),
+                defid: 2,
+                thumbs_up: 3,
+                thumbs_down: 7,
+                written_on: "".to_string(),
+            },
+        ]
+    }
+
+    /// Visible text of a MarkdownV2 message, without markup, link targets and escapes.
+    fn markdown_plain_text(message: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = message.chars().peekable();
+        let mut line_start = true;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => plain.extend(chars.next()),
+                '*' | '_' | '~' | '|' | '`' | '[' => {}
+                '>' if line_start => {}
+                ']' if chars.peek() == Some(&'(') => {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\\' => { chars.next(); }
+                            ')' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                _ => plain.push(c),
+            }
+            line_start = c == '\n' || (line_start && c == '*');
+        }
+        plain
+    }
+
+    /// Visible text of an HTML message, without tags and with entities decoded.
+    fn html_plain_text(message: &str) -> String {
+        let mut plain = String::new();
+        let mut in_tag = false;
+        for c in message.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' if in_tag => in_tag = false,
+                _ if !in_tag => plain.push(c),
+                _ => {}
+            }
+        }
+        plain.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+    }
+
+    #[test]
+    fn test_golden_definitions_markdown_v2() {
+        let message = definitions_fixture().build_message(&RenderOptions::default()).render(Format::MarkdownV2);
+        assert_eq!(message, include_str!("telegram/golden/definitions.md"));
+    }
+
+    #[test]
+    fn test_golden_definitions_html() {
+        let message = definitions_fixture().build_message(&RenderOptions::default()).render(Format::Html);
+        assert_eq!(message, include_str!("telegram/golden/definitions.html"));
+    }
+
+    #[test]
+    fn test_golden_urban_markdown_v2() {
+        let message = urban_fixture().build_message(&RenderOptions::default()).render(Format::MarkdownV2);
+        assert_eq!(message, include_str!("telegram/golden/urban.md"));
+    }
+
+    #[test]
+    fn test_golden_urban_html() {
+        let message = urban_fixture().build_message(&RenderOptions::default()).render(Format::Html);
+        assert_eq!(message, include_str!("telegram/golden/urban.html"));
+    }
+
+    #[test]
+    fn test_collapse_long_parts_of_speech() {
+        let options = RenderOptions { collapse_threshold: 1, ..Default::default() };
+        let message = definitions_fixture().build_message(&options).render(Format::MarkdownV2);
+        assert_eq!(message, include_str!("telegram/golden/definitions_collapsed.md"));
+
+        let message = definitions_fixture().build_message(&options).render(Format::Html);
+        assert_eq!(message, include_str!("telegram/golden/definitions_collapsed.html"));
+    }
+
+    #[test]
+    fn test_metrics_under_definitions_header() {
+        let options = RenderOptions { show_metrics: true, ..Default::default() };
+        let message = definitions_fixture().build_message(&options).render(Format::Html);
+        assert!(message.starts_with("<b>Definitions for</b> <i>set</i>:\n<i>1 syllable · very common · A1</i>\n"));
+        assert!(!definitions_fixture().build_message(&RenderOptions::default()).render(Format::Html).contains("syllable"));
+    }
+
+    #[test]
+    fn test_word_metrics() {
+        let metrics = WordMetrics {
+            word: "house".to_string(),
+            syllables: 1,
+            rank: Some(160),
+            band: crate::metrics::Band::VeryCommon,
+            level: crate::metrics::Level::A1,
+        };
+
+        assert_eq!(
+            metrics.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>Level of</b> <i>house</i>:\n<b>Syllables:</b> 1\n<b>Frequency:</b> very common (rank 160)\n<b>CEFR:</b> A1 (estimated)\n",
+        );
+    }
+
+    #[test]
+    fn test_collapse_keeps_parts_of_speech_within_threshold() {
+        let options = RenderOptions { collapse_threshold: 2, ..Default::default() };
+        let collapsed = definitions_fixture().build_message(&options);
+        let expanded = definitions_fixture().build_message(&RenderOptions::default());
+        assert_eq!(collapsed, expanded);
+    }
+
+    #[test]
+    fn test_urban_references_become_deep_links() {
+        let options = RenderOptions { bot_username: Some("wordbot".to_string()), ..Default::default() };
+        let message = urban_fixture().build_message(&options).render(Format::MarkdownV2);
+        assert_eq!(message, include_str!("telegram/golden/urban_links.md"));
+
+        let message = urban_fixture().build_message(&options).render(Format::Html);
+        assert_eq!(message, include_str!("telegram/golden/urban_links.html"));
+    }
+
+    #[test]
+    fn test_urban_offensive_words_are_masked() {
+        let mut definitions = urban_fixture();
+        definitions[0].example = "What a [sh1t] wheelsucker, f*cking hell".to_string();
+        let options = RenderOptions { mask_offensive: true, bot_username: Some("wordbot".to_string()), ..Default::default() };
+
+        let message = definitions.build_message(&options).render(Format::MarkdownV2);
+        assert!(message.contains("\nWhat a ||sh1t|| wheelsucker, ||f\\*cking|| hell\n"));
+
+        let message = definitions.build_message(&options).render(Format::Html);
+        assert!(message.contains("\nWhat a <tg-spoiler>sh1t</tg-spoiler> wheelsucker, <tg-spoiler>f*cking</tg-spoiler> hell\n"));
+    }
+
+    #[test]
+    fn test_markdown_v2_and_html_show_the_same_text() {
+        let documents = [
+            definitions_fixture().build_message(&RenderOptions::default()),
+            urban_fixture().build_message(&RenderOptions::default()),
+            definitions_fixture().build_message(&RenderOptions { collapse_threshold: 1, ..Default::default() }),
+            urban_fixture().build_message(&RenderOptions { bot_username: Some("wordbot".to_string()), ..Default::default() }),
+        ];
+        for document in documents {
+            let markdown = markdown_plain_text(&document.render(Format::MarkdownV2));
+            let html = html_plain_text(&document.render(Format::Html));
+            assert_eq!(markdown, html);
+        }
+    }
+
+    #[test]
+    fn test_sentence_glossary() {
+        use crate::lemma::Lemma;
+        use crate::lookup::{Found, Source};
+
+        let glossary = SentenceGlossary {
+            entries: vec![
+                Found {
+                    term: "sets".to_string(),
+                    source: Source::Dictionary,
+                    answer: Answer::Definitions(definitions_fixture()),
+                    lemma: Some(Lemma { lemma: "set".to_string(), form: "plural or 3rd person singular".to_string() }),
+                    phrase_kind: None,
+                },
+                Found {
+                    term: "wheelsucker".to_string(),
+                    source: Source::Urban,
+                    answer: Answer::Urban(urban_fixture()),
+                    lemma: None,
+                    phrase_kind: None,
+                },
+            ],
+            missing: vec!["qwzx".to_string()],
+        };
+
+        let message = glossary.build_message(&RenderOptions::default()).render(Format::MarkdownV2);
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("*sets* → set — _verb_ "));
+        assert!(lines[1].starts_with("*wheelsucker* — _urban_ A road cyclist who stays"));
+        assert_eq!(lines[2], "_No definition found for: qwzx_");
+    }
+
+    #[test]
+    fn test_definition_shows_broader_and_narrower_terms() {
+        let definition = Definition {
+            word: "dog".to_string(),
+            meanings: vec![Meaning {
+                part_of_speech: "noun".to_string(),
+                synonyms: Vec::new(),
+                antonyms: Vec::new(),
+                inflections: Vec::new(),
+                definitions: vec![DefinitionDetail {
+                    definition: "a member of the genus Canis".to_string(),
+                    antonyms: None,
+                    synonyms: None,
+                    example: None,
+                    hypernyms: vec!["canine".to_string()],
+                    hyponyms: vec!["puppy".to_string(), "hunting dog".to_string()],
+                }],
+            }],
+            origin: None,
+        };
+
+        assert_eq!(
+            definition.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>[noun]</b>\n- a member of the genus Canis\n<i>kind of canine; such as puppy, hunting dog</i>\n\n",
+        );
+    }
+
+    #[test]
+    fn test_reverse_candidates_are_numbered() {
+        let candidates = vec![
+            Candidate { word: "insomnia".to_string(), definition: "chronic inability to sleep".to_string(), score: 2.0 },
+            Candidate { word: "nap".to_string(), definition: "a short sleep".to_string(), score: 1.0 },
+        ];
+        let options = RenderOptions { bot_username: Some("wordbot".to_string()), ..Default::default() };
+
+        assert_eq!(
+            candidates.build_message(&options).render(Format::Html),
+            "1. <a href=\"https://t.me/wordbot?start=info_insomnia\">insomnia</a> — chronic inability to sleep\n2. <a href=\"https://t.me/wordbot?start=info_nap\">nap</a> — a short sleep\n",
+        );
+    }
+
+    #[test]
+    fn test_word_groups_show_syllables() {
+        let groups = WordGroups {
+            title: "Rhymes for",
+            word: "cat".to_string(),
+            syllables: 1,
+            groups: vec![("perfect rhymes".to_string(), vec![("hat".to_string(), 1), ("acrobat".to_string(), 3)])],
+        };
+
+        assert_eq!(
+            groups.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>Rhymes for</b> <i>cat</i> (1 syllable):\n<b>[perfect rhymes]</b> hat (1), acrobat (3)\n",
+        );
+    }
+
+    #[test]
+    fn test_anagrams_show_scores() {
+        let anagrams = Anagrams {
+            letters: "listen".to_string(),
+            groups: vec![
+                ("full anagrams".to_string(), vec![("silent".to_string(), 6)]),
+                ("3 letters".to_string(), vec![("tin".to_string(), 3)]),
+            ],
+        };
+
+        assert_eq!(
+            anagrams.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>Anagrams of</b> <i>listen</i> (Scrabble points):\n<b>[full anagrams]</b> silent (6)\n<b>[3 letters]</b> tin (3)\n",
+        );
+    }
+
+    #[test]
+    fn test_validity_per_list() {
+        let validity = Validity {
+            word: "qi".to_string(),
+            lists: vec![
+                ("enable".to_string(), Verdict::NotInExcerpt),
+                ("collins".to_string(), Verdict::Valid),
+                ("twl".to_string(), Verdict::NotValid),
+            ],
+            scrabble: 11,
+            words_with_friends: 11,
+        };
+
+        assert_eq!(
+            validity.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>qi</b>:\n<b>[enable]</b> not in bundled excerpt\n<b>[collins]</b> valid\n<b>[twl]</b> not valid\nScrabble: 11 points, Words with Friends: 11 points\n",
+        );
+    }
+
+    #[test]
+    fn test_pattern_matches_show_page() {
+        let matches = Matches {
+            query: "c?t".to_string(),
+            words: vec!["cat".to_string(), "cot".to_string()],
+            page: 1,
+            pages: 2,
+            total: 32,
+        };
+
+        assert_eq!(
+            matches.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>Words matching</b> <i>c?t</i> (32):\ncat, cot\n<i>Page 2 of 2</i>\n",
+        );
+    }
+
+    #[test]
+    fn test_etymology_highlights_languages_and_dates() {
+        let etymology = Etymology {
+            word: "dog".to_string(),
+            source: crate::lookup::Source::Wiktionary,
+            text: "From Old English docga (c. 1050)".to_string(),
+        };
+
+        assert_eq!(
+            etymology.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>Origin of</b> <i>dog</i>:\nFrom <b>Old English</b> docga (<i>c. 1050</i>)\n<i>From Wiktionary</i>\n",
+        );
+    }
+
+    #[test]
+    fn test_translations_group_by_language_or_sense() {
+        let translation = |lang: &str, word: &str, sense: &str| Translation {
+            lang: lang.to_string(),
+            code: String::new(),
+            word: word.to_string(),
+            sense: Some(sense.to_string()),
+        };
+        let translations = vec![
+            translation("French", "chien", "animal"),
+            translation("German", "Hund", "animal"),
+            translation("French", "chienne", "animal"),
+        ];
+        assert_eq!(
+            translations.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>[French]</b> chien, chienne\n<b>[German]</b> Hund\n",
+        );
+
+        let french = vec![translation("French", "chien", "animal"), translation("French", "suivre", "follow")];
+        assert_eq!(
+            french.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>[animal]</b> chien\n<b>[follow]</b> suivre\n",
+        );
+    }
+
+    #[test]
+    fn test_definition_shows_inflections_but_not_origin() {
+        let definition = Definition {
+            word: "dog".to_string(),
+            meanings: vec![Meaning {
+                part_of_speech: "noun".to_string(),
+                synonyms: Vec::new(),
+                antonyms: Vec::new(),
+                inflections: vec!["plural dogs".to_string()],
+                definitions: vec![DefinitionDetail {
+                    definition: "A mammal.".to_string(),
+                    antonyms: None,
+                    synonyms: None,
+                    example: None,
+                    hypernyms: Vec::new(),
+                    hyponyms: Vec::new(),
+                }],
+            }],
+            origin: Some("From Old English docga.".to_string()),
+        };
+
+        assert_eq!(
+            definition.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>[noun]</b> <i>plural dogs</i>\n- A mammal.\n\n",
+        );
+    }
+
+    #[test]
+    fn test_related_words_link_to_lookups() {
+        let related = RelatedWords {
+            word: "fast".to_string(),
+            relation: crate::thesaurus::Relation::Antonyms,
+            groups: vec![("adjective".to_string(), vec!["slow".to_string(), "sluggish".to_string()])],
+        };
+        let options = RenderOptions { bot_username: Some("wordbot".to_string()), ..Default::default() };
+
+        assert_eq!(
+            related.build_message(&options).render(Format::MarkdownV2),
+            "*Antonyms of* _fast_:\n*\\[adjective\\]* [slow](https://t.me/wordbot?start=info_slow), [sluggish](https://t.me/wordbot?start=info_sluggish)\n",
+        );
+        assert_eq!(
+            related.build_message(&RenderOptions::default()).render(Format::Html),
+            "<b>Antonyms of</b> <i>fast</i>:\n<b>[adjective]</b> slow, sluggish\n",
+        );
+    }
+
+    #[test]
+    fn test_summary_cuts_long_text() {
+        assert_eq!(summary("\nfirst line\nsecond"), "first line");
+        assert_eq!(summary(&"a".repeat(SUMMARY_LENGTH + 10)), format!("{}…", "a".repeat(SUMMARY_LENGTH)));
     }
 }
@@ -0,0 +1,606 @@
+use std::borrow::Cow;
+
+use crate::telegram::Format;
+
+/// Telegram rejects messages longer than 4096 UTF-16 code units.
+pub const MAX_MESSAGE_LENGTH: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnitKind {
+    Text,
+    Space,
+    Newline,
+    Marker,
+}
+
+/// Smallest piece of a message that may never be split: a character, an escape sequence or
+/// HTML entity, a formatting marker or tag, or a whole inline link.
+#[derive(Debug)]
+struct Unit<'a> {
+    text: &'a str,
+    width: usize,
+    kind: UnitKind,
+}
+
+impl<'a> Unit<'a> {
+    fn new(text: &'a str, kind: UnitKind) -> Self {
+        let kind = match (kind, text) {
+            (UnitKind::Text, " ") => UnitKind::Space,
+            (UnitKind::Text, "\n") => UnitKind::Newline,
+            _ => kind,
+        };
+        Unit { text, width: utf16_len(text), kind }
+    }
+
+    fn is_whitespace(&self) -> bool {
+        self.kind == UnitKind::Space || self.kind == UnitKind::Newline
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Boundary {
+    Char,
+    Word,
+    Line,
+    Paragraph,
+    Sense,
+}
+
+struct Candidate<'a> {
+    boundary: Boundary,
+    used: usize,
+    end: usize,
+    next: usize,
+    stack: Vec<&'a str>,
+    hidden: usize,
+}
+
+/// Starts an expandable blockquote, which ends with `||` at the end of its last line.
+const EXPANDABLE_BLOCKQUOTE: &str = "**>";
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+fn closer_for(marker: &str) -> Cow<'_, str> {
+    if marker.starts_with("```") {
+        Cow::Borrowed("```")
+    } else if marker == EXPANDABLE_BLOCKQUOTE {
+        Cow::Borrowed("||")
+    } else if is_opening_tag(marker) {
+        let name: String = marker[1..].chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+        Cow::Owned(format!("</{}>", name))
+    } else {
+        Cow::Borrowed(marker)
+    }
+}
+
+fn is_opening_tag(marker: &str) -> bool {
+    marker.starts_with('<') && !marker.starts_with("</")
+}
+
+fn closers_width(stack: &[&str]) -> usize {
+    stack.iter().map(|m| utf16_len(&closer_for(m))).sum()
+}
+
+fn closers(stack: &[&str]) -> String {
+    stack.iter().rev().map(|m| closer_for(m)).collect()
+}
+
+/// What a marker did to the open entities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MarkerEffect {
+    Opened,
+    /// Closed the entity at this position of the stack.
+    Closed(usize),
+    /// Closing tag without any open entity.
+    Stray,
+}
+
+/// Opens or closes the entity a marker stands for.
+///
+/// MarkdownV2 markers toggle their entity, HTML tags open or close it explicitly.
+fn apply_marker<'a>(stack: &mut Vec<&'a str>, marker: &'a str) -> MarkerEffect {
+    if !is_opening_tag(marker) {
+        let closer = closer_for(marker);
+        if let Some(position) = stack.iter().rposition(|m| closer_for(m) == closer) {
+            stack.remove(position);
+            return MarkerEffect::Closed(position);
+        }
+        if marker.starts_with("</") {
+            return MarkerEffect::Stray;
+        }
+    }
+    stack.push(marker);
+    MarkerEffect::Opened
+}
+
+fn closes_open_entity(stack: &[&str], unit: &Unit) -> bool {
+    unit.kind == UnitKind::Marker
+        && !is_opening_tag(unit.text)
+        && stack.iter().any(|m| closer_for(m) == closer_for(unit.text))
+}
+
+/// Length in bytes of an inline link `[text](url)` starting at the beginning of `text`.
+fn link_length(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    let mut escaped = false;
+
+    loop {
+        let (_, c) = chars.next()?;
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ']' {
+            break;
+        }
+    }
+
+    if chars.next()?.1 != '(' {
+        return None;
+    }
+
+    for (i, c) in chars {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ')' {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+fn tokenize_markdown(message: &str, max_length: usize) -> Vec<Unit<'_>> {
+    let mut units = Vec::new();
+    let mut in_code: Option<&str> = None;
+    let mut i = 0;
+
+    while i < message.len() {
+        let rest = &message[i..];
+        let c = rest.chars().next().unwrap();
+        let mut kind = UnitKind::Text;
+
+        let len = if c == '\\' {
+            c.len_utf8() + rest[1..].chars().next().map_or(0, char::len_utf8)
+        } else if let Some(closer) = in_code {
+            if rest.starts_with(closer) {
+                in_code = None;
+                kind = UnitKind::Marker;
+                closer.len()
+            } else {
+                c.len_utf8()
+            }
+        } else if rest.starts_with("```") {
+            in_code = Some("```");
+            kind = UnitKind::Marker;
+            match rest.find('\n') {
+                Some(newline) if newline < 32 => newline + 1,
+                _ => 3,
+            }
+        } else if c == '`' {
+            in_code = Some("`");
+            kind = UnitKind::Marker;
+            1
+        } else if rest.starts_with(EXPANDABLE_BLOCKQUOTE) && (i == 0 || message[..i].ends_with('\n')) {
+            kind = UnitKind::Marker;
+            EXPANDABLE_BLOCKQUOTE.len()
+        } else if rest.starts_with("__") || rest.starts_with("||") {
+            kind = UnitKind::Marker;
+            2
+        } else if c == '*' || c == '_' || c == '~' {
+            kind = UnitKind::Marker;
+            1
+        } else if c == '[' {
+            match link_length(rest) {
+                Some(len) if utf16_len(&rest[..len]) <= max_length / 2 => len,
+                _ => 1,
+            }
+        } else {
+            c.len_utf8()
+        };
+
+        units.push(Unit::new(&rest[..len], kind));
+        i += len;
+    }
+
+    units
+}
+
+fn tokenize_html(message: &str) -> Vec<Unit<'_>> {
+    let mut units = Vec::new();
+    let mut i = 0;
+
+    while i < message.len() {
+        let rest = &message[i..];
+        let c = rest.chars().next().unwrap();
+        let mut kind = UnitKind::Text;
+
+        let len = match c {
+            '<' => match rest.find('>') {
+                Some(end) => {
+                    kind = UnitKind::Marker;
+                    end + 1
+                }
+                None => 1,
+            },
+            '&' => match rest.find(';') {
+                Some(end) if end <= 10 => end + 1,
+                _ => 1,
+            },
+            _ => c.len_utf8(),
+        };
+
+        units.push(Unit::new(&rest[..len], kind));
+        i += len;
+    }
+
+    units
+}
+
+fn tokenize(message: &str, format: Format, max_length: usize) -> Vec<Unit<'_>> {
+    match format {
+        Format::MarkdownV2 => tokenize_markdown(message, max_length),
+        Format::Html => tokenize_html(message),
+    }
+}
+
+/// Classifies the whitespace run starting at `start`, returning its kind and the first unit after it.
+fn whitespace_run(units: &[Unit], start: usize) -> (Boundary, usize) {
+    let mut end = start;
+    let mut newlines = 0;
+    while end < units.len() && units[end].is_whitespace() {
+        if units[end].kind == UnitKind::Newline {
+            newlines += 1;
+        }
+        end += 1;
+    }
+
+    let boundary = match newlines {
+        0 => Boundary::Word,
+        1 => Boundary::Line,
+        _ if units.get(end).is_some_and(|u| u.kind == UnitKind::Marker) => Boundary::Sense,
+        _ => Boundary::Paragraph,
+    };
+    (boundary, end)
+}
+
+fn skip_whitespace(units: &[Unit], mut index: usize) -> usize {
+    while index < units.len() && units[index].is_whitespace() {
+        index += 1;
+    }
+    index
+}
+
+/// Markers re-opening `open` at the start of a chunk beginning with `first`. A chunk starting on
+/// a quoted line already has the `>` of a re-opened expandable blockquote.
+fn reopening(open: &[&str], first: &Unit) -> String {
+    open.iter()
+        .map(|m| if *m == EXPANDABLE_BLOCKQUOTE && first.text == ">" { "**" } else { m })
+        .collect()
+}
+
+/// Splits a message rendered in `format` into chunks of at most `max_length` UTF-16 code units.
+///
+/// Breaks are placed at the most meaningful boundary available in the second half of a chunk
+/// (sense, paragraph, line, then word), never inside an escape sequence or a link, and any
+/// formatting still open at a break is closed at the end of the chunk and re-opened at the
+/// start of the next one. When the re-opened formatting leaves no room for the first unit, its
+/// outermost entities are left closed in that chunk, and a link still too long is split.
+pub fn split_message(message: &str, format: Format, max_length: usize) -> Vec<String> {
+    let mut units = tokenize(message, format, max_length);
+    let halfway = max_length / 2;
+    let mut chunks = Vec::new();
+    let mut start = skip_whitespace(&units, 0);
+    let mut open: Vec<&str> = Vec::new();
+
+    while start < units.len() {
+        // Markers at the start of a chunk only change the formatting to re-open: an entity closed
+        // there already was at the end of the previous chunk, and one opened there can be left
+        // closed like the others.
+        while units.get(start).is_some_and(|unit| unit.kind == UnitKind::Marker && apply_marker(&mut open, unit.text) != MarkerEffect::Stray) {
+            start = skip_whitespace(&units, start + 1);
+        }
+        if start == units.len() {
+            break;
+        }
+
+        // Entities at the bottom of `open` that are not re-opened in this chunk.
+        let mut hidden = 0;
+        loop {
+            let visible = &open[hidden..];
+            let needed = utf16_len(&reopening(visible, &units[start])) + units[start].width + closers_width(visible);
+            if needed <= max_length {
+                break;
+            }
+            if hidden < open.len() {
+                hidden += 1;
+            } else if units[start].kind == UnitKind::Text && units[start].text.chars().count() > 1 {
+                let text = units[start].text;
+                let pieces: Vec<Unit> = text.char_indices().map(|(i, c)| Unit::new(&text[i..i + c.len_utf8()], UnitKind::Text)).collect();
+                units.splice(start..start + 1, pieces);
+            } else {
+                break;
+            }
+        }
+
+        let prefix = reopening(&open[hidden..], &units[start]);
+        let mut used = utf16_len(&prefix);
+        let mut stack = open.clone();
+        // Closing markers of hidden entities, left out of the chunk.
+        let mut skipped: Vec<usize> = Vec::new();
+        let mut best: Option<Candidate> = None;
+        let mut fallback: Option<Candidate> = None;
+        let mut opened_last = false;
+        let mut index = start;
+
+        while index < units.len() {
+            let unit = &units[index];
+
+            if index > start && used + closers_width(&stack[hidden..]) <= max_length {
+                let (boundary, next) = if unit.is_whitespace() {
+                    whitespace_run(&units, index)
+                } else {
+                    (Boundary::Char, index)
+                };
+                let candidate = Candidate { boundary, used, end: index, next, stack: stack.clone(), hidden };
+
+                let after_whitespace = units[index - 1].is_whitespace();
+                let creates_empty_entity = opened_last
+                    || units.get(next).is_some_and(|u| closes_open_entity(&stack, u));
+                let better = best.as_ref().is_none_or(|b| {
+                    (used >= halfway, boundary) >= (b.used >= halfway, b.boundary)
+                });
+
+                if !after_whitespace && !creates_empty_entity && better {
+                    best = Some(candidate);
+                } else {
+                    fallback = Some(candidate);
+                }
+            }
+
+            if used + unit.width > max_length && index > start {
+                break;
+            }
+
+            opened_last = false;
+            match unit.kind {
+                UnitKind::Marker => match apply_marker(&mut stack, unit.text) {
+                    MarkerEffect::Closed(position) if position < hidden => {
+                        hidden -= 1;
+                        skipped.push(index);
+                    }
+                    effect => {
+                        used += unit.width;
+                        opened_last = effect == MarkerEffect::Opened;
+                    }
+                },
+                _ => used += unit.width,
+            }
+            index += 1;
+        }
+
+        let body = |end: usize| -> String {
+            (start..end).filter(|i| !skipped.contains(i)).map(|i| units[i].text).collect()
+        };
+
+        if index == units.len() {
+            chunks.push(format!("{}{}", prefix, body(index).trim_end_matches([' ', '\n'])));
+            break;
+        }
+
+        let (end, next, stack_at_end, hidden_at_end) = match best.or(fallback) {
+            Some(candidate) => (candidate.end, candidate.next, candidate.stack, candidate.hidden),
+            None => (index, index, stack, hidden),
+        };
+
+        chunks.push(format!("{}{}{}", prefix, body(end), closers(&stack_at_end[hidden_at_end..])));
+
+        start = skip_whitespace(&units, next);
+        open = stack_at_end;
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::telegram::html::escape;
+    use crate::telegram::markdown::escape_text;
+
+    /// Text of a message with formatting markers and the whitespace around breaks removed.
+    fn visible_text(message: &str, format: Format) -> String {
+        tokenize(message, format, MAX_MESSAGE_LENGTH)
+            .iter()
+            .filter(|u| u.kind == UnitKind::Text)
+            .map(|u| u.text)
+            .collect()
+    }
+
+    fn assert_balanced(chunk: &str, format: Format) {
+        let mut stack = Vec::new();
+        for unit in tokenize(chunk, format, MAX_MESSAGE_LENGTH) {
+            if unit.kind == UnitKind::Marker {
+                apply_marker(&mut stack, unit.text);
+            }
+        }
+        assert!(stack.is_empty(), "unbalanced chunk: {:?}", chunk);
+    }
+
+    #[test]
+    fn test_short_message_is_not_split() {
+        let chunks = split_message("*hello* world", Format::MarkdownV2, MAX_MESSAGE_LENGTH);
+        assert_eq!(chunks, vec!["*hello* world"]);
+    }
+
+    #[test]
+    fn test_split_prefers_line_over_word() {
+        let message = "first line is here\nsecond line";
+        let chunks = split_message(message, Format::MarkdownV2, 24);
+        assert_eq!(chunks, vec!["first line is here", "second line"]);
+    }
+
+    #[test]
+    fn test_split_prefers_sense_over_line() {
+        let message = "*[noun]*\n\\- one\n\\- two\n\n*[verb]*\n\\- three";
+        let chunks = split_message(message, Format::MarkdownV2, 40);
+        assert_eq!(chunks, vec!["*[noun]*\n\\- one\n\\- two", "*[verb]*\n\\- three"]);
+    }
+
+    #[test]
+    fn test_split_counts_utf16_units() {
+        let message = "😀".repeat(5);
+        let chunks = split_message(&message, Format::MarkdownV2, 4);
+        assert_eq!(chunks, vec!["😀😀", "😀😀", "😀"]);
+    }
+
+    #[test]
+    fn test_split_never_breaks_escape() {
+        let message = "ab\\.\\.\\.";
+        let chunks = split_message(message, Format::MarkdownV2, 3);
+        assert_eq!(chunks, vec!["ab", "\\.", "\\.", "\\."]);
+    }
+
+    #[test]
+    fn test_split_reopens_formatting() {
+        let message = "*bold words here*";
+        let chunks = split_message(message, Format::MarkdownV2, 12);
+        assert_eq!(chunks, vec!["*bold words*", "*here*"]);
+    }
+
+    #[test]
+    fn test_split_reopens_expandable_blockquote() {
+        let message = "*\\[verb\\]*\n\\- one\n**>\\- two\n>\\- three||\n";
+        let chunks = split_message(message, Format::MarkdownV2, 30);
+        assert_eq!(chunks, vec!["*\\[verb\\]*\n\\- one\n**>\\- two||", "**>\\- three||"]);
+    }
+
+    #[test]
+    fn test_split_keeps_links_whole() {
+        let message = format!("{}[road cyclist](https://t.me/x)", "word ".repeat(8));
+        let chunks = split_message(&message, Format::MarkdownV2, 64);
+        assert_eq!(chunks, vec!["word word word word word word word word", "[road cyclist](https://t.me/x)"]);
+    }
+
+    #[test]
+    fn test_split_html_reopens_tags() {
+        let message = "<b>bold <i>words</i> here</b>";
+        let chunks = split_message(message, Format::Html, 24);
+        assert_eq!(chunks, vec!["<b>bold <i>words</i></b>", "<b>here</b>"]);
+    }
+
+    #[test]
+    fn test_split_html_never_breaks_entity() {
+        let message = "a&amp;&amp;";
+        let chunks = split_message(message, Format::Html, 5);
+        assert_eq!(chunks, vec!["a", "&amp;", "&amp;"]);
+    }
+
+    fn formatted_message() -> impl Strategy<Value = String> {
+        let piece = (any::<String>(), 0..6usize).prop_map(|(text, style)| {
+            let text = escape_text(&format!("x{}", text));
+            match style {
+                0 => format!("*{}* ", text),
+                1 => format!("_{}_ ", text),
+                2 => format!("||{}|| ", text),
+                3 => format!("\n\n{}\n", text),
+                _ => format!("{} ", text),
+            }
+        });
+        prop::collection::vec(piece, 0..40).prop_map(|pieces| pieces.concat())
+    }
+
+    fn formatted_html_message() -> impl Strategy<Value = String> {
+        let piece = (any::<String>(), 0..5usize).prop_map(|(text, style)| {
+            let text = escape(&format!("x{}", text));
+            match style {
+                0 => format!("<b>{}</b> ", text),
+                1 => format!("<i>x <b>{}</b></i> ", text),
+                2 => format!("<a href=\"https://t.me/x\">{}</a> ", text),
+                3 => format!("\n\n{}\n", text),
+                _ => format!("{} ", text),
+            }
+        });
+        prop::collection::vec(piece, 0..40).prop_map(|pieces| pieces.concat())
+    }
+
+    fn nested_message() -> impl Strategy<Value = String> {
+        // An entity cannot be nested in itself, so each marker appears once per piece.
+        let markers = prop::sample::subsequence(vec!["*", "_", "~", "||"], 1..=4).prop_shuffle();
+        let piece = (prop::collection::vec("[a-z]{1,6}", 1..6), markers).prop_map(|(words, markers)| {
+            let opening: String = markers.concat();
+            let closing: String = markers.iter().rev().copied().collect();
+            format!("{}{}{} ", opening, words.join(" "), closing)
+        });
+        prop::collection::vec(piece, 1..10).prop_map(|pieces| pieces.concat())
+    }
+
+    #[test]
+    fn test_split_leaves_deep_formatting_closed_when_it_cannot_fit() {
+        let message = "*_~||abcdefgh||~_*";
+        let chunks = split_message(message, Format::MarkdownV2, 8);
+        for chunk in &chunks {
+            assert!(utf16_len(chunk) <= 8, "{:?} is too long", chunk);
+            assert_balanced(chunk, Format::MarkdownV2);
+        }
+        let joined: String = chunks.iter().map(|c| visible_text(c, Format::MarkdownV2)).collect();
+        assert_eq!(joined, "abcdefgh");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_nested_formatting_fits_small_chunks(message in nested_message(), max_length in 6..24usize) {
+            let chunks = split_message(&message, Format::MarkdownV2, max_length);
+
+            for chunk in &chunks {
+                prop_assert!(utf16_len(chunk) <= max_length, "{:?} is longer than {}", chunk, max_length);
+                assert_balanced(chunk, Format::MarkdownV2);
+            }
+
+            let original: String = visible_text(&message, Format::MarkdownV2).split_whitespace().collect();
+            let joined: String = chunks.iter().map(|c| visible_text(c, Format::MarkdownV2)).collect::<String>().split_whitespace().collect();
+            prop_assert_eq!(original, joined);
+        }
+
+        #[test]
+        fn prop_chunks_fit_and_preserve_text(message in formatted_message(), max_length in 16..300usize) {
+            let chunks = split_message(&message, Format::MarkdownV2, max_length);
+
+            for chunk in &chunks {
+                prop_assert!(utf16_len(chunk) <= max_length);
+                prop_assert!(!chunk.is_empty());
+                assert_balanced(chunk, Format::MarkdownV2);
+            }
+
+            let original: String = visible_text(&message, Format::MarkdownV2).split_whitespace().collect();
+            let joined: String = chunks.iter().map(|c| visible_text(c, Format::MarkdownV2)).collect::<String>().split_whitespace().collect();
+            prop_assert_eq!(original, joined);
+        }
+
+        #[test]
+        fn prop_html_chunks_fit_and_preserve_text(message in formatted_html_message(), max_length in 64..300usize) {
+            let chunks = split_message(&message, Format::Html, max_length);
+
+            for chunk in &chunks {
+                prop_assert!(utf16_len(chunk) <= max_length);
+                assert_balanced(chunk, Format::Html);
+            }
+
+            let original: String = visible_text(&message, Format::Html).split_whitespace().collect();
+            let joined: String = chunks.iter().map(|c| visible_text(c, Format::Html)).collect::<String>().split_whitespace().collect();
+            prop_assert_eq!(original, joined);
+        }
+
+        #[test]
+        fn prop_arbitrary_input_never_panics(message in any::<String>(), max_length in 64..300usize) {
+            for chunk in split_message(&message, Format::MarkdownV2, max_length) {
+                prop_assert!(utf16_len(&chunk) <= max_length);
+            }
+        }
+    }
+}
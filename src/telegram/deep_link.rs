@@ -0,0 +1,113 @@
+/// Telegram only accepts start payloads of up to 64 characters.
+const MAX_PAYLOAD_LENGTH: usize = 64;
+
+/// Lookup requested by a `/start` payload, as sent when a deep link to the bot is opened.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartPayload {
+    Info(String),
+    Urban(String),
+}
+
+impl StartPayload {
+    fn prefix(&self) -> &'static str {
+        match self {
+            StartPayload::Info(_) => "info_",
+            StartPayload::Urban(_) => "urban_",
+        }
+    }
+
+    fn term(&self) -> &str {
+        match self {
+            StartPayload::Info(term) | StartPayload::Urban(term) => term,
+        }
+    }
+
+    /// Encodes the lookup as a payload: letters and digits are kept, spaces become `_` and any
+    /// other byte becomes `-` followed by two hex digits. Returns `None` when it does not fit.
+    pub fn encode(&self) -> Option<String> {
+        let mut payload = String::from(self.prefix());
+        for byte in self.term().bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => payload.push(byte as char),
+                b' ' => payload.push('_'),
+                _ => payload.push_str(&format!("-{:02X}", byte)),
+            }
+        }
+        (payload.len() <= MAX_PAYLOAD_LENGTH).then_some(payload)
+    }
+
+    /// Decodes a payload produced by [`StartPayload::encode`].
+    pub fn decode(payload: &str) -> Option<StartPayload> {
+        if let Some(encoded) = payload.strip_prefix("info_") {
+            decode_term(encoded).map(StartPayload::Info)
+        } else if let Some(encoded) = payload.strip_prefix("urban_") {
+            decode_term(encoded).map(StartPayload::Urban)
+        } else {
+            None
+        }
+    }
+
+    /// Link that opens a chat with the bot and runs this lookup.
+    pub fn link(&self, bot_username: &str) -> Option<String> {
+        self.encode().map(|payload| format!("https://t.me/{}?start={}", bot_username, payload))
+    }
+}
+
+fn decode_term(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut chars = encoded.bytes();
+
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'_' => bytes.push(b' '),
+            b'-' => {
+                let hex = [chars.next()?, chars.next()?];
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+
+    let term = String::from_utf8(bytes).ok()?;
+    (!term.trim().is_empty()).then_some(term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip() {
+        for term in ["road cyclist", "she can", "rock'n'roll", "café", "snake_case"] {
+            for payload in [StartPayload::Urban(term.to_string()), StartPayload::Info(term.to_string())] {
+                let encoded = payload.encode().unwrap();
+                assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'));
+                assert_eq!(StartPayload::decode(&encoded), Some(payload));
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_payload() {
+        assert_eq!(StartPayload::Urban("road cyclist".to_string()).encode().as_deref(), Some("urban_road_cyclist"));
+        assert_eq!(StartPayload::Info("a'b".to_string()).encode().as_deref(), Some("info_a-27b"));
+        assert_eq!(StartPayload::Urban("x".repeat(60)).encode(), None);
+    }
+
+    #[test]
+    fn test_deep_link() {
+        let link = StartPayload::Urban("she can".to_string()).link("wordbot");
+        assert_eq!(link.as_deref(), Some("https://t.me/wordbot?start=urban_she_can"));
+    }
+
+    #[test]
+    fn test_decode_rejects_other_payloads() {
+        assert_eq!(StartPayload::decode("hello"), None);
+        assert_eq!(StartPayload::decode("urban_a-2"), None);
+        assert_eq!(StartPayload::decode("urban_a-+1"), None);
+        assert_eq!(StartPayload::decode("info_"), None);
+    }
+}
@@ -0,0 +1,112 @@
+use teloxide::types::ParseMode;
+
+use crate::telegram::{html, markdown};
+
+/// Markup language a [`Document`] is rendered to before being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Format {
+    #[default]
+    MarkdownV2,
+    Html,
+}
+
+impl Format {
+    pub fn parse_mode(self) -> ParseMode {
+        match self {
+            Format::MarkdownV2 => ParseMode::MarkdownV2,
+            Format::Html => ParseMode::Html,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Code(String),
+    Link { text: String, url: String },
+    Spoiler(String),
+    Blockquote(Document),
+    ExpandableBlockquote(Document),
+    Newline,
+}
+
+/// Format independent message: a sequence of plain text pieces, each tagged with the entity it
+/// is shown as. Escaping happens only when the document is rendered.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Document {
+    nodes: Vec<Node>,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        self.push(Node::Text(text.to_string()))
+    }
+
+    pub fn bold(&mut self, text: &str) -> &mut Self {
+        self.push(Node::Bold(text.to_string()))
+    }
+
+    pub fn italic(&mut self, text: &str) -> &mut Self {
+        self.push(Node::Italic(text.to_string()))
+    }
+
+    pub fn code(&mut self, text: &str) -> &mut Self {
+        self.push(Node::Code(text.to_string()))
+    }
+
+    pub fn link(&mut self, text: &str, url: &str) -> &mut Self {
+        self.push(Node::Link { text: text.to_string(), url: url.to_string() })
+    }
+
+    pub fn spoiler(&mut self, text: &str) -> &mut Self {
+        self.push(Node::Spoiler(text.to_string()))
+    }
+
+    /// Adds a quote block; every line of `text` becomes a quoted line.
+    pub fn blockquote(&mut self, text: &str) -> &mut Self {
+        self.push(Node::Blockquote(Document::from_lines(text)))
+    }
+
+    /// Adds a quote block that Telegram shows collapsed until it is tapped.
+    pub fn expandable_blockquote(&mut self, text: &str) -> &mut Self {
+        self.push(Node::ExpandableBlockquote(Document::from_lines(text)))
+    }
+
+    pub fn newline(&mut self) -> &mut Self {
+        self.push(Node::Newline)
+    }
+
+    /// Appends an already built document.
+    pub fn append(&mut self, other: &Document) -> &mut Self {
+        self.nodes.extend(other.nodes.iter().cloned());
+        self
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::MarkdownV2 => markdown::render(&self.nodes),
+            Format::Html => html::render(&self.nodes),
+        }
+    }
+
+    fn from_lines(text: &str) -> Document {
+        let mut document = Document::new();
+        document.text(&text.lines().collect::<Vec<_>>().join("\n"));
+        document
+    }
+
+    fn push(&mut self, node: Node) -> &mut Self {
+        self.nodes.push(node);
+        self
+    }
+}
@@ -0,0 +1,98 @@
+use crate::telegram::document::Node;
+
+/// Escapes text placed anywhere in an HTML message, including attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders document nodes using the subset of HTML supported by Telegram.
+pub fn render(nodes: &[Node]) -> String {
+    let mut buffer = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => buffer.push_str(&escape(text)),
+            Node::Bold(text) => buffer.push_str(&format!("<b>{}</b>", escape(text))),
+            Node::Italic(text) => buffer.push_str(&format!("<i>{}</i>", escape(text))),
+            Node::Code(text) => buffer.push_str(&format!("<code>{}</code>", escape(text))),
+            Node::Link { text, url } => buffer.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), escape(text))),
+            Node::Spoiler(text) => buffer.push_str(&format!("<tg-spoiler>{}</tg-spoiler>", escape(text))),
+            Node::Blockquote(content) | Node::ExpandableBlockquote(content) => {
+                let inner = render(content.nodes());
+                if inner.trim().is_empty() {
+                    continue;
+                }
+                if !buffer.is_empty() && !buffer.ends_with('\n') {
+                    buffer.push('\n');
+                }
+                let tag = if matches!(node, Node::ExpandableBlockquote(_)) { "blockquote expandable" } else { "blockquote" };
+                buffer.push_str(&format!("<{}>{}</blockquote>\n", tag, inner.trim_end_matches('\n')));
+            }
+            Node::Newline => buffer.push('\n'),
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telegram::{Document, Format};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a < b && \"c\" > d"), "a &lt; b &amp;&amp; &quot;c&quot; &gt; d");
+    }
+
+    #[test]
+    fn test_escape_leaves_markdown_characters() {
+        assert_eq!(escape("hello-world! (*_*)"), "hello-world! (*_*)");
+    }
+
+    #[test]
+    fn test_render_entities() {
+        let message = Document::new()
+            .bold("Word:")
+            .text(" ")
+            .italic("x<y")
+            .text(" ")
+            .spoiler("secret")
+            .newline()
+            .code("a & b")
+            .render(Format::Html);
+        assert_eq!(message, "<b>Word:</b> <i>x&lt;y</i> <tg-spoiler>secret</tg-spoiler>\n<code>a &amp; b</code>");
+    }
+
+    #[test]
+    fn test_render_link_escapes_url() {
+        let message = Document::new().link("JKu", "https://example.com/?a=1&b=\"2\"").render(Format::Html);
+        assert_eq!(message, "<a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">JKu</a>");
+    }
+
+    #[test]
+    fn test_expandable_blockquote() {
+        let message = Document::new().expandable_blockquote("first\nsecond").render(Format::Html);
+        assert_eq!(message, "<blockquote expandable>first\nsecond</blockquote>\n");
+    }
+
+    #[test]
+    fn test_empty_blockquote_is_skipped() {
+        let message = Document::new().text("intro").newline().expandable_blockquote("").text("after").render(Format::Html);
+        assert_eq!(message, "intro\nafter");
+    }
+
+    #[test]
+    fn test_blockquote() {
+        let message = Document::new().text("intro").blockquote("first\nsecond").render(Format::Html);
+        assert_eq!(message, "intro\n<blockquote>first\nsecond</blockquote>\n");
+    }
+}
@@ -0,0 +1,132 @@
+use crate::telegram::document::Node;
+
+const SPECIAL_CHARS: [char; 19] = [
+    '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+fn escape_with(text: &str, special_chars: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special_chars.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes text placed outside of code spans and link URLs.
+pub fn escape_text(text: &str) -> String {
+    escape_with(text, &SPECIAL_CHARS)
+}
+
+/// Escapes text placed inside `code` and ```pre``` entities.
+pub fn escape_code(text: &str) -> String {
+    escape_with(text, &['\\', '`'])
+}
+
+/// Escapes the URL part of an inline link.
+pub fn escape_url(url: &str) -> String {
+    escape_with(url, &['\\', ')'])
+}
+
+/// Renders document nodes as MarkdownV2, escaping every piece of text for the entity it is placed in.
+pub fn render(nodes: &[Node]) -> String {
+    let mut buffer = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => buffer.push_str(&escape_text(text)),
+            Node::Bold(text) => buffer.push_str(&format!("*{}*", escape_text(text))),
+            Node::Italic(text) => buffer.push_str(&format!("_{}_", escape_text(text))),
+            Node::Code(text) => buffer.push_str(&format!("`{}`", escape_code(text))),
+            Node::Link { text, url } => buffer.push_str(&format!("[{}]({})", escape_text(text), escape_url(url))),
+            Node::Spoiler(text) => buffer.push_str(&format!("||{}||", escape_text(text))),
+            Node::Blockquote(content) | Node::ExpandableBlockquote(content) => {
+                let inner = render(content.nodes());
+                // An empty quote would render as a bare `**||`, which Telegram rejects.
+                if inner.trim().is_empty() {
+                    continue;
+                }
+                if !buffer.is_empty() && !buffer.ends_with('\n') {
+                    buffer.push('\n');
+                }
+                let lines: Vec<String> = inner.lines().map(|line| format!(">{}", line)).collect();
+                if matches!(node, Node::ExpandableBlockquote(_)) {
+                    buffer.push_str(&format!("**{}||\n", lines.join("\n")));
+                } else {
+                    buffer.push_str(&format!("{}\n", lines.join("\n")));
+                }
+            }
+            Node::Newline => buffer.push('\n'),
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telegram::{Document, Format};
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("hello-world!"), "hello\\-world\\!");
+    }
+
+    #[test]
+    fn test_escape_text_with_special_chars() {
+        assert_eq!(escape_text("####()!!"), "\\#\\#\\#\\#\\(\\)\\!\\!");
+    }
+
+    #[test]
+    fn test_escape_text_with_no_special_chars() {
+        assert_eq!(escape_text("hello world"), "hello world");
+    }
+
+    #[test]
+    fn test_escape_text_with_backslash() {
+        assert_eq!(escape_text("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn test_code_only_escapes_backtick_and_backslash() {
+        let message = Document::new().code("a `b` \\ c.d").render(Format::MarkdownV2);
+        assert_eq!(message, "`a \\`b\\` \\\\ c.d`");
+    }
+
+    #[test]
+    fn test_link_escapes_text_and_url() {
+        let message = Document::new().link("road (cyclist)", "https://example.com/a_(b)").render(Format::MarkdownV2);
+        assert_eq!(message, "[road \\(cyclist\\)](https://example.com/a_(b\\))");
+    }
+
+    #[test]
+    fn test_blockquote_quotes_every_line() {
+        let message = Document::new().text("intro").blockquote("first.\nsecond").render(Format::MarkdownV2);
+        assert_eq!(message, "intro\n>first\\.\n>second\n");
+    }
+
+    #[test]
+    fn test_expandable_blockquote() {
+        let message = Document::new().text("intro").expandable_blockquote("first.\nsecond").render(Format::MarkdownV2);
+        assert_eq!(message, "intro\n**>first\\.\n>second||\n");
+    }
+
+    #[test]
+    fn test_empty_expandable_blockquote_is_skipped() {
+        let message = Document::new().text("intro").newline().expandable_blockquote(" \n").text("after").render(Format::MarkdownV2);
+        assert_eq!(message, include_str!("golden/empty_blockquote.md"));
+    }
+
+    #[test]
+    fn test_render_chains_entities() {
+        let message = Document::new()
+            .bold("Word:")
+            .text(" ")
+            .italic("x_y")
+            .text(" ")
+            .spoiler("secret!")
+            .render(Format::MarkdownV2);
+        assert_eq!(message, "*Word:* _x\\_y_ ||secret\\!||");
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+use crate::dictionary::Definition;
+
+const BUNDLED_THESAURUS: &str = include_str!("thesaurus/thesaurus.tsv");
+
+/// Which related words a thesaurus lookup asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relation {
+    Synonyms,
+    Antonyms,
+}
+
+impl Relation {
+    pub fn label(self) -> &'static str {
+        match self {
+            Relation::Synonyms => "synonyms",
+            Relation::Antonyms => "antonyms",
+        }
+    }
+}
+
+/// Synonyms and antonyms of a word for one part of speech.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThesaurusEntry {
+    pub part_of_speech: String,
+    pub synonyms: Vec<String>,
+    pub antonyms: Vec<String>,
+}
+
+impl ThesaurusEntry {
+    fn words(&self, relation: Relation) -> &[String] {
+        match relation {
+            Relation::Synonyms => &self.synonyms,
+            Relation::Antonyms => &self.antonyms,
+        }
+    }
+}
+
+/// Local thesaurus completing the related words dictionaries return.
+#[derive(Debug, Default)]
+pub struct Thesaurus {
+    entries: HashMap<String, Vec<ThesaurusEntry>>,
+}
+
+impl Thesaurus {
+    /// Reads lines of `word<TAB>part of speech<TAB>synonyms<TAB>antonyms`, with comma separated
+    /// lists, skipping `#` comments.
+    pub fn parse(source: &str) -> Self {
+        let mut thesaurus = Thesaurus::default();
+        for (number, line) in source.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let [word, part_of_speech, ..] = fields[..] else {
+                warn!("Skipping malformed thesaurus line {}", number + 1);
+                continue;
+            };
+            let list = |index: usize| -> Vec<String> {
+                fields.get(index).map(|list| split_list(list)).unwrap_or_default()
+            };
+            thesaurus.entries.entry(word.to_lowercase()).or_default().push(ThesaurusEntry {
+                part_of_speech: part_of_speech.to_string(),
+                synonyms: list(2),
+                antonyms: list(3),
+            });
+        }
+        thesaurus
+    }
+
+    /// Bundled thesaurus, extended with the entries of the file named by `WORDBOT_THESAURUS`, if any.
+    pub fn global() -> &'static Thesaurus {
+        static THESAURUS: OnceLock<Thesaurus> = OnceLock::new();
+        THESAURUS.get_or_init(|| {
+            let mut source = BUNDLED_THESAURUS.to_string();
+            if let Ok(path) = env::var("WORDBOT_THESAURUS") {
+                match fs::read_to_string(&path) {
+                    Ok(extra) => {
+                        info!("Loaded thesaurus from {}", path);
+                        source.push('\n');
+                        source.push_str(&extra);
+                    }
+                    Err(err) => warn!("Failed to read thesaurus {}: {}", path, err),
+                }
+            }
+            Thesaurus::parse(&source)
+        })
+    }
+
+    pub fn get(&self, word: &str) -> &[ThesaurusEntry] {
+        self.entries.get(&word.trim().to_lowercase()).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split([',', ';']).map(str::trim).filter(|word| !word.is_empty()).map(str::to_string).collect()
+}
+
+/// Related words of a word, grouped by part of speech.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelatedWords {
+    pub word: String,
+    pub relation: Relation,
+    pub groups: Vec<(String, Vec<String>)>,
+}
+
+impl RelatedWords {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    fn add(&mut self, part_of_speech: &str, words: &[String]) {
+        for word in words {
+            if word.eq_ignore_ascii_case(&self.word) {
+                continue;
+            }
+            let index = match self.groups.iter().position(|(group, _)| group == part_of_speech) {
+                Some(index) => index,
+                None => {
+                    self.groups.push((part_of_speech.to_string(), Vec::new()));
+                    self.groups.len() - 1
+                }
+            };
+            let group = &mut self.groups[index].1;
+            if !group.iter().any(|known| known.eq_ignore_ascii_case(word)) {
+                group.push(word.clone());
+            }
+        }
+    }
+}
+
+/// Gathers the `relation` words of `word` from every meaning and sense of `definitions`, then
+/// from `thesaurus`, grouped by part of speech in the order they first appear and without repeats.
+pub fn collect(word: &str, definitions: &[Definition], relation: Relation, thesaurus: &Thesaurus) -> RelatedWords {
+    let mut related = RelatedWords { word: word.to_string(), relation, groups: Vec::new() };
+    for meaning in definitions.iter().flat_map(|definition| &definition.meanings) {
+        let meaning_words = match relation {
+            Relation::Synonyms => &meaning.synonyms,
+            Relation::Antonyms => &meaning.antonyms,
+        };
+        related.add(&meaning.part_of_speech, meaning_words);
+        for detail in &meaning.definitions {
+            let detail_words = match relation {
+                Relation::Synonyms => &detail.synonyms,
+                Relation::Antonyms => &detail.antonyms,
+            };
+            related.add(&meaning.part_of_speech, detail_words.as_deref().unwrap_or_default());
+        }
+    }
+    for entry in thesaurus.get(word) {
+        related.add(&entry.part_of_speech, entry.words(relation));
+    }
+    related
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{DefinitionDetail, Meaning};
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn definitions() -> Vec<Definition> {
+        let detail = |synonyms: &[&str], antonyms: &[&str]| DefinitionDetail {
+            definition: "definition".to_string(),
+            synonyms: Some(strings(synonyms)),
+            antonyms: Some(strings(antonyms)),
+            example: None,
+            hypernyms: Vec::new(),
+            hyponyms: Vec::new(),
+        };
+        vec![Definition {
+            word: "fast".to_string(),
+            meanings: vec![
+                Meaning {
+                    part_of_speech: "adjective".to_string(),
+                    definitions: vec![detail(&["quick", "Fast"], &["slow"]), detail(&["Quick", "rapid"], &[])],
+                    synonyms: strings(&["speedy"]),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                },
+                Meaning {
+                    part_of_speech: "verb".to_string(),
+                    definitions: vec![detail(&["starve"], &["eat"])],
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    inflections: Vec::new(),
+                },
+            ],
+            origin: None,
+        }]
+    }
+
+    #[test]
+    fn test_collect_groups_and_deduplicates() {
+        let related = collect("fast", &definitions(), Relation::Synonyms, &Thesaurus::default());
+        assert_eq!(related.groups, vec![
+            ("adjective".to_string(), strings(&["speedy", "quick", "rapid"])),
+            ("verb".to_string(), strings(&["starve"])),
+        ]);
+    }
+
+    #[test]
+    fn test_collect_adds_thesaurus_words() {
+        let thesaurus = Thesaurus::parse("# comment\nfast\tadjective\tswift, rapid\tsluggish; slow\nfast\tnoun\t\tfeast\n");
+        let related = collect("fast", &definitions(), Relation::Antonyms, &thesaurus);
+        assert_eq!(related.groups, vec![
+            ("adjective".to_string(), strings(&["slow", "sluggish"])),
+            ("verb".to_string(), strings(&["eat"])),
+            ("noun".to_string(), strings(&["feast"])),
+        ]);
+    }
+
+    #[test]
+    fn test_bundled_thesaurus() {
+        let entries = Thesaurus::parse(BUNDLED_THESAURUS);
+        assert!(entries.get("Happy")[0].synonyms.contains(&"glad".to_string()));
+        assert!(entries.get("unknown").is_empty());
+    }
+}
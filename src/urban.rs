@@ -1,26 +1,208 @@
+use std::str::FromStr;
+
 use serde::Deserialize;
 
 use crate::dictionary::DictionaryError;
-use crate::urban::rest::urban_dictionary::get_urban_definition;
+use crate::urban::rest::urban_dictionary::{get_random_definitions, get_urban_definition, get_words_of_the_day};
 
+mod filter;
+mod references;
 mod rest;
 
+pub use filter::{ContentFilter, NsfwPolicy, Rating};
+pub use references::{parse_references, Segment};
+
 #[derive(Debug, Deserialize)]
 pub struct UrbanDefinition {
     pub word: String,
     pub definition: String,
     pub example: String,
     pub author: String,
+    #[serde(default)]
     pub permalink: String,
+    #[serde(default)]
+    pub defid: u64,
+    #[serde(default)]
+    pub thumbs_up: u32,
+    #[serde(default)]
+    pub thumbs_down: u32,
+    #[serde(default)]
+    pub written_on: String,
 }
+
+impl UrbanDefinition {
+    /// Net votes of the entry.
+    pub fn score(&self) -> i64 {
+        i64::from(self.thumbs_up) - i64::from(self.thumbs_down)
+    }
+
+    /// Id of the entry, read from its permalink when the payload has no `defid`.
+    pub fn id(&self) -> u64 {
+        if self.defid != 0 {
+            return self.defid;
+        }
+        self.permalink.rsplit_once("defid=").and_then(|(_, id)| id.parse().ok()).unwrap_or_default()
+    }
+
+    /// Day the entry was written, as `YYYY-MM-DD`.
+    pub fn date(&self) -> &str {
+        self.written_on.get(..10).unwrap_or(&self.written_on)
+    }
+}
+
+/// Order in which urban definitions are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UrbanSort {
+    /// As returned by Urban Dictionary.
+    #[default]
+    Upstream,
+    /// Highest net votes first.
+    Votes,
+    /// Most recently written first.
+    Newest,
+}
+
+impl FromStr for UrbanSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "upstream" | "default" => Ok(UrbanSort::Upstream),
+            "votes" | "score" => Ok(UrbanSort::Votes),
+            "newest" | "date" => Ok(UrbanSort::Newest),
+            other => Err(format!("Unknown sort order: {}", other)),
+        }
+    }
+}
+
+/// Drops definitions scoring below `min_score` and orders the rest by `sort`.
+pub fn arrange(mut definitions: Vec<UrbanDefinition>, sort: UrbanSort, min_score: Option<i64>) -> Vec<UrbanDefinition> {
+    if let Some(min_score) = min_score {
+        definitions.retain(|definition| definition.score() >= min_score);
+    }
+
+    match sort {
+        UrbanSort::Upstream => {}
+        UrbanSort::Votes => definitions.sort_by_key(|definition| std::cmp::Reverse(definition.score())),
+        // Ids grow over time, so they order entries written on the same day.
+        UrbanSort::Newest => definitions.sort_by(|a, b| b.written_on.cmp(&a.written_on).then(b.id().cmp(&a.id()))),
+    }
+    definitions
+}
+
 pub async fn definition(word: &str) -> Result<Vec<UrbanDefinition>, DictionaryError> {
     get_urban_definition(word).await
 }
 
+/// Urban entries served without a search term.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UrbanFeed {
+    Random,
+    WordsOfTheDay,
+}
+
+impl UrbanFeed {
+    pub async fn fetch(self) -> Result<Vec<UrbanDefinition>, DictionaryError> {
+        match self {
+            UrbanFeed::Random => get_random_definitions().await,
+            UrbanFeed::WordsOfTheDay => get_words_of_the_day().await,
+        }
+    }
+
+    /// Times the feed is fetched while looking for an entry the chat has not seen; only random
+    /// entries change between requests.
+    pub fn attempts(self) -> usize {
+        match self {
+            UrbanFeed::Random => 3,
+            UrbanFeed::WordsOfTheDay => 1,
+        }
+    }
+}
+
+/// First definition whose id is not in `seen`.
+pub fn first_unseen(definitions: Vec<UrbanDefinition>, seen: &[u64]) -> Option<UrbanDefinition> {
+    definitions.into_iter().find(|definition| !seen.contains(&definition.id()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn entry(defid: u64, thumbs_up: u32, thumbs_down: u32, written_on: &str) -> UrbanDefinition {
+        UrbanDefinition {
+            word: "word".to_string(),
+            definition: "definition".to_string(),
+            example: "".to_string(),
+            author: "author".to_string(),
+            permalink: format!("https://www.urbandictionary.com/define.php?term=word&defid={}", defid),
+            defid,
+            thumbs_up,
+            thumbs_down,
+            written_on: written_on.to_string(),
+        }
+    }
+
+    fn fixture() -> Vec<UrbanDefinition> {
+        vec![
+            entry(1, 10, 8, "2005-01-01T00:00:00.000Z"),
+            entry(2, 50, 5, "2003-06-15T00:00:00.000Z"),
+            entry(3, 1, 9, "2020-11-30T00:00:00.000Z"),
+            entry(4, 12, 10, "2005-01-01T00:00:00.000Z"),
+        ]
+    }
+
+    fn ids(definitions: &[UrbanDefinition]) -> Vec<u64> {
+        definitions.iter().map(|definition| definition.defid).collect()
+    }
+
+    #[test]
+    fn test_score_and_date() {
+        let definition = entry(1, 3, 7, "2006-05-31T00:00:00.000Z");
+        assert_eq!(definition.score(), -4);
+        assert_eq!(definition.date(), "2006-05-31");
+    }
+
+    #[test]
+    fn test_id_falls_back_to_permalink() {
+        assert_eq!(entry(7, 0, 0, "").id(), 7);
+        let mut definition = entry(0, 0, 0, "");
+        definition.permalink = "https://www.urbandictionary.com/define.php?term=word&defid=1766772".to_string();
+        assert_eq!(definition.id(), 1766772);
+    }
+
+    #[test]
+    fn test_arrange_keeps_upstream_order() {
+        assert_eq!(ids(&arrange(fixture(), UrbanSort::Upstream, None)), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_arrange_by_votes() {
+        assert_eq!(ids(&arrange(fixture(), UrbanSort::Votes, None)), vec![2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn test_arrange_by_newest() {
+        assert_eq!(ids(&arrange(fixture(), UrbanSort::Newest, None)), vec![3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn test_arrange_filters_low_scores() {
+        assert_eq!(ids(&arrange(fixture(), UrbanSort::Upstream, Some(2))), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_first_unseen_skips_seen_ids() {
+        assert_eq!(first_unseen(fixture(), &[1, 2]).map(|definition| definition.defid), Some(3));
+        assert!(first_unseen(fixture(), &[1, 2, 3, 4]).is_none());
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!("Votes".parse::<UrbanSort>(), Ok(UrbanSort::Votes));
+        assert_eq!("newest".parse::<UrbanSort>(), Ok(UrbanSort::Newest));
+        assert!("random".parse::<UrbanSort>().is_err());
+    }
+
     #[tokio::test]
     async fn test_get_hello_definition() {
         let word = "hello";
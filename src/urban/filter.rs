@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+use crate::urban::UrbanDefinition;
+
+const BUNDLED_WORDS: &str = include_str!("nsfw_words.txt");
+
+/// Endings accepted after a listed word, so "fucking" or "bitches" need no entry of their own.
+const SUFFIXES: [&str; 9] = ["s", "es", "ed", "er", "ers", "ing", "in", "y", "ies"];
+
+/// Listed words shorter than this only match exactly, to keep "cumin" or "assess" clean.
+const MIN_PREFIX_LENGTH: usize = 4;
+
+/// What a chat does with explicit urban definitions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NsfwPolicy {
+    /// Show definitions verbatim.
+    Allow,
+    /// Hide offensive words behind spoilers.
+    Mask,
+    /// Drop explicit definitions altogether.
+    Hide,
+}
+
+impl NsfwPolicy {
+    /// Policy of chats that never chose one: private chats see everything, groups get masking.
+    pub fn default_for(is_private: bool) -> Self {
+        if is_private { NsfwPolicy::Allow } else { NsfwPolicy::Mask }
+    }
+}
+
+impl FromStr for NsfwPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "allow" => Ok(NsfwPolicy::Allow),
+            "mask" => Ok(NsfwPolicy::Mask),
+            "hide" => Ok(NsfwPolicy::Hide),
+            other => Err(format!("Unknown NSFW policy: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rating {
+    Clean,
+    Explicit,
+}
+
+/// Local word list based classifier for urban content.
+#[derive(Debug)]
+pub struct ContentFilter {
+    words: HashSet<String>,
+    collapsed: HashSet<String>,
+}
+
+impl ContentFilter {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words: HashSet<String> = words.into_iter()
+            .map(|word| word.as_ref().trim().to_lowercase())
+            .filter(|word| !word.is_empty() && !word.starts_with('#'))
+            .collect();
+        let collapsed = words.iter().map(|word| collapse_repeats(word)).collect();
+        ContentFilter { words, collapsed }
+    }
+
+    /// Filter shared by the whole bot: the bundled list plus the words in the file named by
+    /// `WORDBOT_NSFW_WORDS`, if any.
+    pub fn global() -> &'static ContentFilter {
+        static FILTER: OnceLock<ContentFilter> = OnceLock::new();
+        FILTER.get_or_init(|| {
+            let mut words: Vec<String> = BUNDLED_WORDS.lines().map(str::to_string).collect();
+            if let Ok(path) = env::var("WORDBOT_NSFW_WORDS") {
+                match fs::read_to_string(&path) {
+                    Ok(extra) => {
+                        info!("Loaded NSFW word list from {}", path);
+                        words.extend(extra.lines().map(str::to_string));
+                    }
+                    Err(err) => warn!("Failed to read NSFW word list {}: {}", path, err),
+                }
+            }
+            ContentFilter::new(words)
+        })
+    }
+
+    pub fn is_offensive(&self, token: &str) -> bool {
+        let normalized = normalize(token);
+        if normalized.contains('*') {
+            return self.words.iter().any(|word| {
+                matches_masked(&normalized, word)
+                    || (word.len() >= MIN_PREFIX_LENGTH
+                        && SUFFIXES.iter().any(|suffix| matches_masked(&normalized, &format!("{}{}", word, suffix))))
+            });
+        }
+
+        self.words.contains(&normalized)
+            || (has_emphasis(&normalized) && self.collapsed.contains(&collapse_repeats(&normalized)))
+            || self.words.iter().any(|word| {
+                word.len() >= MIN_PREFIX_LENGTH
+                    && normalized.strip_prefix(word.as_str()).is_some_and(|suffix| is_suffix_of(word, suffix))
+            })
+    }
+
+    /// Byte ranges of the offensive words in `text`.
+    pub fn offensive_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        tokens(text).into_iter().filter(|range| self.is_offensive(&text[range.clone()])).collect()
+    }
+
+    pub fn classify(&self, definition: &UrbanDefinition) -> Rating {
+        let texts = [&definition.word, &definition.definition, &definition.example];
+        if texts.iter().any(|text| !self.offensive_ranges(text).is_empty()) {
+            Rating::Explicit
+        } else {
+            Rating::Clean
+        }
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '*' || c == '@' || c == '$'
+}
+
+fn tokens(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (is_token_char(c), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push(s..text.len());
+    }
+    ranges
+}
+
+/// Lowercases a token and undoes common letter substitutions such as "sh1t" or "@ss".
+fn normalize(token: &str) -> String {
+    let lower = token.to_lowercase();
+    if !lower.chars().any(char::is_alphabetic) {
+        return lower;
+    }
+    lower.chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Squeezes letters repeated for emphasis, as in "fuuuuck".
+fn collapse_repeats(word: &str) -> String {
+    let mut collapsed = String::with_capacity(word.len());
+    for c in word.chars() {
+        if !collapsed.ends_with(c) {
+            collapsed.push(c);
+        }
+    }
+    collapsed
+}
+
+/// Matches censored spellings such as "f*ck" or "sh**" against a listed word.
+fn matches_masked(token: &str, word: &str) -> bool {
+    let token: Vec<char> = token.chars().collect();
+    let word: Vec<char> = word.chars().collect();
+    token.len() == word.len()
+        && token.first().is_some_and(|c| *c != '*')
+        && token.iter().zip(&word).all(|(t, w)| *t == '*' || t == w)
+}
+
+/// Whether `suffix` is an accepted ending of `word`, allowing a doubled last letter as in "frakking".
+fn is_suffix_of(word: &str, suffix: &str) -> bool {
+    if SUFFIXES.contains(&suffix) {
+        return true;
+    }
+    match (word.chars().last(), suffix.chars().next()) {
+        (Some(last), Some(first)) if last == first => SUFFIXES.contains(&&suffix[first.len_utf8()..]),
+        _ => false,
+    }
+}
+
+/// Whether a letter is repeated at least three times in a row, as people do for emphasis.
+fn has_emphasis(word: &str) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    chars.windows(3).any(|w| w[0] == w[1] && w[1] == w[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> ContentFilter {
+        ContentFilter::new(BUNDLED_WORDS.lines())
+    }
+
+    fn entry(definition: &str, example: &str) -> UrbanDefinition {
+        UrbanDefinition {
+            word: "word".to_string(),
+            definition: definition.to_string(),
+            example: example.to_string(),
+            author: "author".to_string(),
+            permalink: "https://www.urbandictionary.com/define.php?term=word&defid=1".to_string(),
+            defid: 1,
+            thumbs_up: 0,
+            thumbs_down: 0,
+            written_on: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plain_words() {
+        let filter = filter();
+        assert!(filter.is_offensive("shit"));
+        assert!(filter.is_offensive("Shit"));
+        assert!(!filter.is_offensive("ship"));
+        assert!(!filter.is_offensive("as"));
+    }
+
+    #[test]
+    fn test_inflections() {
+        let filter = filter();
+        assert!(filter.is_offensive("fucking"));
+        assert!(filter.is_offensive("bitches"));
+        assert!(!filter.is_offensive("cumin"));
+        assert!(!filter.is_offensive("assess"));
+    }
+
+    #[test]
+    fn test_obfuscated_spellings() {
+        let filter = filter();
+        assert!(filter.is_offensive("sh1t"));
+        assert!(filter.is_offensive("@ss"));
+        assert!(filter.is_offensive("fuuuuck"));
+        assert!(filter.is_offensive("f*ck"));
+        assert!(!filter.is_offensive("****"));
+        assert!(!filter.is_offensive("1337"));
+    }
+
+    #[test]
+    fn test_offensive_ranges() {
+        let text = "What the f*ck, this is sh1t!";
+        let ranges: Vec<&str> = filter().offensive_ranges(text).into_iter().map(|range| &text[range]).collect();
+        assert_eq!(ranges, vec!["f*ck", "sh1t"]);
+    }
+
+    #[test]
+    fn test_classify() {
+        let filter = filter();
+        assert_eq!(filter.classify(&entry("A [road cyclist] who drafts.", "George is one.")), Rating::Clean);
+        assert_eq!(filter.classify(&entry("A lazy cyclist.", "What a wanker.")), Rating::Explicit);
+    }
+
+    #[test]
+    fn test_custom_word_list() {
+        let filter = ContentFilter::new(["# comment", "Frak", ""]);
+        assert!(filter.is_offensive("frakking"));
+        assert!(!filter.is_offensive("shit"));
+    }
+
+    #[test]
+    fn test_policy() {
+        assert_eq!(NsfwPolicy::default_for(true), NsfwPolicy::Allow);
+        assert_eq!(NsfwPolicy::default_for(false), NsfwPolicy::Mask);
+        assert_eq!("HIDE".parse::<NsfwPolicy>(), Ok(NsfwPolicy::Hide));
+        assert!("block".parse::<NsfwPolicy>().is_err());
+    }
+}
@@ -0,0 +1,62 @@
+/// Piece of an urban text: plain text or a `[term]` cross reference to another entry.
+#[derive(Debug, PartialEq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Reference(&'a str),
+}
+
+/// Splits Urban Dictionary text into plain text and `[term]` cross references.
+pub fn parse_references(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut search_from = 0;
+
+    while let Some(open) = text[search_from..].find('[').map(|i| search_from + i) {
+        let Some(close) = text[open..].find(']').map(|i| open + i) else {
+            break;
+        };
+        let term = &text[open + 1..close];
+        if term.is_empty() || term.contains('[') {
+            search_from = open + 1;
+            continue;
+        }
+        if open > text_start {
+            segments.push(Segment::Text(&text[text_start..open]));
+        }
+        segments.push(Segment::Reference(term));
+        text_start = close + 1;
+        search_from = text_start;
+    }
+
+    if text_start < text.len() {
+        segments.push(Segment::Text(&text[text_start..]));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_references() {
+        let segments = parse_references("A [road cyclist] who stays behind other cyclists' wheels so that he/[she can] draft");
+        assert_eq!(segments, vec![
+            Segment::Text("A "),
+            Segment::Reference("road cyclist"),
+            Segment::Text(" who stays behind other cyclists' wheels so that he/"),
+            Segment::Reference("she can"),
+            Segment::Text(" draft"),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_references_keeps_unbalanced_brackets() {
+        let segments = parse_references("a [] b [c [d] e]");
+        assert_eq!(segments, vec![
+            Segment::Text("a [] b [c "),
+            Segment::Reference("d"),
+            Segment::Text(" e]"),
+        ]);
+    }
+}
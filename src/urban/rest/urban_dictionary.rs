@@ -1,4 +1,5 @@
 use std::env;
+use reqwest::Url;
 use serde::Deserialize;
 use crate::urban::UrbanDefinition;
 use crate::dictionary::{DictionaryError, DictionaryErrorKind};
@@ -8,7 +9,33 @@ pub struct UrbanDictionaryResponse {
     pub list: Vec<UrbanDefinition>,
 
 }
+
+const HOST: &str = "mashape-community-urban-dictionary.p.rapidapi.com";
+
 pub async fn get_urban_definition(word: &str) -> Result<Vec<UrbanDefinition>, DictionaryError> {
+    get_urban_list(urban_url("define", Some(word))).await
+}
+
+/// A batch of random definitions.
+pub async fn get_random_definitions() -> Result<Vec<UrbanDefinition>, DictionaryError> {
+    get_urban_list(urban_url("random", None)).await
+}
+
+/// Recent words of the day, newest first.
+pub async fn get_words_of_the_day() -> Result<Vec<UrbanDefinition>, DictionaryError> {
+    get_urban_list(urban_url("words_of_the_day", None)).await
+}
+
+/// URL of an endpoint, with `term` percent encoded into the query.
+fn urban_url(endpoint: &str, term: Option<&str>) -> Url {
+    let mut url = Url::parse(&format!("https://{}/{}", HOST, endpoint)).expect("valid urban URL");
+    if let Some(term) = term {
+        url.query_pairs_mut().append_pair("term", term);
+    }
+    url
+}
+
+async fn get_urban_list(url: Url) -> Result<Vec<UrbanDefinition>, DictionaryError> {
     let urban_dictionary_key = match env::var("URBAN_DICTIONARY_KEY") {
         Ok(val) => val,
         Err(_) => return Err(DictionaryError {
@@ -24,10 +51,9 @@ pub async fn get_urban_definition(word: &str) -> Result<Vec<UrbanDefinition>, Di
         });
     }
 
-    let url = format!("https://mashape-community-urban-dictionary.p.rapidapi.com/define?term={}", word);
     let client = reqwest::Client::new();
-    let request = client.get(&url)
-        .header("X-RapidAPI-Host", "mashape-community-urban-dictionary.p.rapidapi.com")
+    let request = client.get(url)
+        .header("X-RapidAPI-Host", HOST)
         .header("X-RapidAPI-Key", &urban_dictionary_key);
     let result = request.send().await;
 
@@ -69,6 +95,12 @@ pub async fn get_urban_definition(word: &str) -> Result<Vec<UrbanDefinition>, Di
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_urban_url_encodes_term() {
+        assert_eq!(urban_url("define", Some("rock & roll#1")).as_str(), format!("https://{}/define?term=rock+%26+roll%231", HOST));
+        assert_eq!(urban_url("random", None).as_str(), format!("https://{}/random", HOST));
+    }
+
     #[tokio::test]
     async fn test_get_urban_definition() {
         let word = "wheelsucker";
@@ -84,5 +116,7 @@ mod tests {
         assert_eq!(definition.example, "George is [the worst] wheelsucker of all, always feigning fatigue when we ask him [to be at] the front every [once in a while].");
         assert_eq!(definition.author, "JKu");
         assert_eq!(definition.permalink, "https://www.urbandictionary.com/define.php?term=wheelsucker&defid=1766772");
+        assert_eq!(definition.defid, 1766772);
+        assert!(definition.written_on.starts_with("2006-"));
     }
 }
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use log::{info, warn};
+
+const BUNDLED_WORDS: &str = include_str!("words/english.txt");
+
+/// English words known without asking any dictionary, used for suggestions and word games.
+#[derive(Debug, Default)]
+pub struct WordList {
+    words: Vec<String>,
+    known: HashSet<String>,
+}
+
+impl WordList {
+    /// Builds a list from lines holding one word each, skipping `#` comments, blank lines and
+    /// repeated words.
+    pub fn new<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut list = WordList::default();
+        for line in lines {
+            let word = line.trim().to_lowercase();
+            if word.is_empty() || word.starts_with('#') || list.known.contains(&word) {
+                continue;
+            }
+            list.known.insert(word.clone());
+            list.words.push(word);
+        }
+        list
+    }
+
+    /// Bundled list, extended with the words of the file named by `WORDBOT_WORD_LIST`, if any.
+    pub fn global() -> &'static WordList {
+        static WORDS: OnceLock<WordList> = OnceLock::new();
+        WORDS.get_or_init(|| {
+            let extra = match env::var("WORDBOT_WORD_LIST") {
+                Ok(path) => match fs::read_to_string(&path) {
+                    Ok(extra) => {
+                        info!("Loaded word list from {}", path);
+                        extra
+                    }
+                    Err(err) => {
+                        warn!("Failed to read word list {}: {}", path, err);
+                        String::new()
+                    }
+                },
+                Err(_) => String::new(),
+            };
+            WordList::new(BUNDLED_WORDS.lines().chain(extra.lines()))
+        })
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.known.contains(&word.trim().to_lowercase())
+    }
+
+    /// Words in the order they were listed.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_skips_comments_and_repeats() {
+        let list = WordList::new(["# header", "Apple", "", "apple", "pear"]);
+        assert_eq!(list.words(), ["apple", "pear"]);
+        assert!(list.contains(" PEAR "));
+        assert!(!list.contains("# header"));
+    }
+
+    #[test]
+    fn test_bundled_list_is_lowercase_words() {
+        let list = WordList::new(BUNDLED_WORDS.lines());
+        assert!(list.words().len() > 1000);
+        assert!(list.words().iter().all(|word| word.chars().all(|c| c.is_ascii_lowercase())));
+    }
+}